@@ -0,0 +1,70 @@
+//! Derive macros for [hashmap_settings](https://docs.rs/hashmap_settings)
+//!
+//! This crate shouldn't be used directly, instead activate the "derive" feature of `hashmap_settings`.
+
+mod setting;
+mod settings;
+
+use proc_macro::TokenStream;
+use syn::{DeriveInput, parse_macro_input};
+
+/// Derive macro for `Setting`
+///
+/// Implements `hashmap_settings::stg::Setting` for the type. When the "serde" feature is activated
+/// the implementation is also registered with `typetag`, so the type can be deserialized as a `Stg`.
+///
+/// The type needs to implement the traits required by `Setting`, and can't be generic.
+///
+/// The name used by `typetag` can be changed with `#[setting(name = "...")]`, by default it's the name of the type.
+///
+/// # Examples
+///
+/// ```ignore
+/// use hashmap_settings::stg::Setting;
+/// # #[cfg(feature = "serde")]
+/// # use serde::{Deserialize, Serialize};
+///
+/// #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// #[derive(Clone, Debug, PartialEq, Setting)]
+/// pub struct MyType {}
+/// ```
+#[proc_macro_derive(Setting, attributes(setting))]
+pub fn derive_setting(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    setting::expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive macro for `Settings`
+///
+/// Implements `hashmap_settings::stg::Settings` for a struct with named fields, where each field
+/// is a setting with the field's name as key. All fields need to implement `Setting`.
+///
+/// Field attributes:
+///
+/// - `#[setting(rename = "...")]` uses a different key for the field.
+///
+/// - `#[setting(default)]` uses `Default::default()` if the setting isn't present.
+///
+/// - `#[setting(default = "path::to::function")]` calls the function if the setting isn't present.
+///
+/// # Examples
+///
+/// ```ignore
+/// use hashmap_settings::stg::Settings;
+///
+/// #[derive(Settings)]
+/// struct EditorSettings {
+///     lines: i32,
+///     #[setting(rename = "editor word", default)]
+///     word: String,
+/// }
+/// ```
+#[proc_macro_derive(Settings, attributes(setting))]
+pub fn derive_settings(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    settings::expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
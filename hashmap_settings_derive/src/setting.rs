@@ -0,0 +1,49 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{DeriveInput, LitStr};
+
+pub fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "`Setting` can't be derived for generic types, implement it manually instead",
+        ));
+    }
+    let name = typetag_name(input)?;
+    let ident = &input.ident;
+    if cfg!(feature = "serde") {
+        let typetag = name.map_or_else(
+            || quote!(#[typetag::serde]),
+            |name| quote!(#[typetag::serde(name = #name)]),
+        );
+        Ok(quote! {
+            const _: () = {
+                use ::hashmap_settings::__private::typetag;
+                #typetag
+                impl ::hashmap_settings::stg::Setting for #ident {}
+            };
+        })
+    } else {
+        Ok(quote! {
+            impl ::hashmap_settings::stg::Setting for #ident {}
+        })
+    }
+}
+
+fn typetag_name(input: &DeriveInput) -> syn::Result<Option<LitStr>> {
+    let mut name = None;
+    for attribute in &input.attrs {
+        if !attribute.path().is_ident("setting") {
+            continue;
+        }
+        attribute.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                name = Some(meta.value()?.parse::<LitStr>()?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported `setting` attribute, expected `name`"))
+            }
+        })?;
+    }
+    Ok(name)
+}
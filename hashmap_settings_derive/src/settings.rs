@@ -0,0 +1,122 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, LitStr, Path};
+
+struct Field<'a> {
+    ident: &'a syn::Ident,
+    ty: &'a syn::Type,
+    key: LitStr,
+    default: Option<TokenStream>,
+}
+
+pub fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "`Settings` can't be derived for generic types",
+        ));
+    }
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            input,
+            "`Settings` can only be derived for structs with named fields",
+        ));
+    };
+    let Fields::Named(named) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            &data.fields,
+            "`Settings` can only be derived for structs with named fields",
+        ));
+    };
+    let fields = named
+        .named
+        .iter()
+        .map(parse_field)
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let ident = &input.ident;
+    let idents = fields.iter().map(|field| field.ident).collect::<Vec<_>>();
+    let reads = fields.iter().map(|field| {
+        let Field {
+            ident,
+            ty,
+            key,
+            default,
+        } = field;
+        let default = default.as_ref().map_or_else(
+            || quote!(::core::option::Option::None),
+            |default| quote!(::core::option::Option::Some(#default)),
+        );
+        quote! {
+            let #ident = ::hashmap_settings::stg::__private::field::<_, _, _, #ty>(
+                account, #key, #default, &mut errors,
+            );
+        }
+    });
+    let writes = fields.iter().map(|Field { ident, key, .. }| {
+        quote! {
+            (#key, ::hashmap_settings::stg::__private::stg(&self.#ident))
+        }
+    });
+    Ok(quote! {
+        impl ::hashmap_settings::stg::Settings for #ident {
+            fn from_account<N, K: ::core::clone::Clone + ::core::cmp::Eq + ::core::hash::Hash + ::core::convert::From<&'static str>, V>(
+                account: &::hashmap_settings::account::Account<N, K, V>,
+            ) -> ::core::result::Result<Self, ::hashmap_settings::stg::SettingsError>
+            where
+                for<'a> ::core::option::Option<&'a V>: ::hashmap_settings::stg::StgTrait,
+            {
+                let mut errors = ::std::vec::Vec::new();
+                #(#reads)*
+                if let (#(::core::option::Option::Some(#idents),)*) = (#(#idents,)*) {
+                    ::core::result::Result::Ok(Self { #(#idents),* })
+                } else {
+                    ::core::result::Result::Err(::hashmap_settings::stg::SettingsError::new(errors))
+                }
+            }
+            fn write_to<'a, N: ::core::clone::Clone + ::core::cmp::PartialEq + 'a, K: ::core::clone::Clone + ::core::cmp::Eq + ::core::hash::Hash + ::core::convert::From<&'static str>, V: ::core::clone::Clone + ::core::convert::From<::hashmap_settings::stg::Stg>>(
+                &self,
+                account: &mut ::hashmap_settings::account::Account<N, K, V>,
+                account_names: impl ::hashmap_settings::account::DeepNames<'a, N>,
+            ) -> ::core::result::Result<(), ::hashmap_settings::account::DeepError> {
+                ::hashmap_settings::stg::__private::write(account, account_names, ::std::vec![#(#writes),*])
+            }
+        }
+    })
+}
+
+fn parse_field(field: &syn::Field) -> syn::Result<Field<'_>> {
+    let ident = field
+        .ident
+        .as_ref()
+        .ok_or_else(|| syn::Error::new_spanned(field, "expected a named field"))?;
+    let mut key = LitStr::new(&ident.to_string(), ident.span());
+    let mut default = None;
+    for attribute in &field.attrs {
+        if !attribute.path().is_ident("setting") {
+            continue;
+        }
+        attribute.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                key = meta.value()?.parse()?;
+                Ok(())
+            } else if meta.path.is_ident("default") {
+                default = Some(if meta.input.peek(syn::Token![=]) {
+                    let path: Path = meta.value()?.parse::<LitStr>()?.parse()?;
+                    quote!(#path)
+                } else {
+                    quote!(::core::default::Default::default)
+                });
+                Ok(())
+            } else {
+                Err(meta.error("unsupported `setting` attribute, expected `rename` or `default`"))
+            }
+        })?;
+    }
+    Ok(Field {
+        ident,
+        ty: &field.ty,
+        key,
+        default,
+    })
+}
@@ -0,0 +1,270 @@
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+use crate::account::{Account, Incrementable, Valid};
+
+/// Builder for [`Account`]s
+///
+/// An `AccountBuilder` allows creating a tree of `Accounts` by chaining methods instead of nesting
+/// calls to [`Account::new`] and [`push`](Account::push).
+///
+/// Child `Accounts` are added as `AccountBuilders` of their own, and the whole tree is only made
+/// [valid](Account#valid) once, when [`build`](AccountBuilder::build) is called.
+///
+/// Unlike [`Account::new`], repeated child names aren't incremented with [`Incrementable`],
+/// instead `build` returns a [`BuildError`].
+///
+/// # Examples
+///
+/// ```
+/// use hashmap_settings::account::AccountBuilder;
+/// let account = AccountBuilder::<String,&str,i32>::new()
+///     .name("Parent".to_string())
+///     .child(
+///         AccountBuilder::new()
+///             .name("Default".to_string())
+///             .setting("lines", 3)
+///             .setting("words", 10)
+///     )
+///     .child(
+///         AccountBuilder::new()
+///             .name("Local".to_string())
+///             .setting("words", 2)
+///     )
+///     .build()?;
+///
+/// assert_eq!(account.get(&"lines"), Some(&3));
+/// assert_eq!(account.get(&"words"), Some(&2));
+/// assert_eq!(account.accounts_names(), vec!["Default", "Local"]);
+/// # Ok::<(), hashmap_settings::account::BuildError<String>>(())
+/// ```
+#[must_use]
+#[allow(clippy::module_name_repetitions)]
+pub struct AccountBuilder<N, K, V> {
+    name: N,
+    active: bool,
+    settings: HashMap<K, V>,
+    accounts: Vec<Self>,
+}
+
+impl<N: Default, K, V> AccountBuilder<N, K, V> {
+    /// Creates a new `AccountBuilder`
+    ///
+    /// The `Account` built will have the [`Default`] name, will be [active](Account#active),
+    /// and won't contain any settings or child `Accounts` unless they are added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::{Account,AccountBuilder};
+    /// let account = AccountBuilder::<String,(),()>::new().build()?;
+    /// assert_eq!(account, Account::default());
+    /// # Ok::<(), hashmap_settings::account::BuildError<String>>(())
+    /// ```
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+impl<N, K, V> AccountBuilder<N, K, V> {
+    /// Sets the [name](Account#name) of the `Account`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::AccountBuilder;
+    /// let account = AccountBuilder::<String,(),()>::new()
+    ///     .name("New Account".to_string())
+    ///     .build()?;
+    /// assert_eq!(account.name(), "New Account");
+    /// # Ok::<(), hashmap_settings::account::BuildError<String>>(())
+    /// ```
+    pub fn name(mut self, name: N) -> Self {
+        self.name = name;
+        self
+    }
+    /// Sets if the `Account` is [active](Account#active)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::AccountBuilder;
+    /// let account = AccountBuilder::<String,(),()>::new()
+    ///     .active(false)
+    ///     .build()?;
+    /// assert!(!account.active());
+    /// # Ok::<(), hashmap_settings::account::BuildError<String>>(())
+    /// ```
+    pub const fn active(mut self, active: bool) -> Self {
+        self.active = active;
+        self
+    }
+    /// Adds a child `Account` on top of the ones already added.
+    ///
+    /// Children added later are in a higher [layer](Account#accounts) and so have priority.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::AccountBuilder;
+    /// let account = AccountBuilder::<i32,&str,i32>::new()
+    ///     .child(AccountBuilder::new().name(1).setting("answer", 0))
+    ///     .child(AccountBuilder::new().name(2).setting("answer", 42))
+    ///     .build()?;
+    /// assert_eq!(account.get(&"answer"), Some(&42));
+    /// # Ok::<(), hashmap_settings::account::BuildError<i32>>(())
+    /// ```
+    pub fn child(mut self, account: Self) -> Self {
+        self.accounts.push(account);
+        self
+    }
+    /// Adds a child `Account` at position `index` of the [`Vec`] of child `Accounts`,
+    /// shifting all `Accounts` after it upwards.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the number of child `Accounts` already added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::AccountBuilder;
+    /// let account = AccountBuilder::<i32,&str,i32>::new()
+    ///     .child(AccountBuilder::new().name(1).setting("answer", 0))
+    ///     .layer_at(0, AccountBuilder::new().name(2).setting("answer", 42))
+    ///     .build()?;
+    /// assert_eq!(account.accounts_names(), vec![&2, &1]);
+    /// assert_eq!(account.get(&"answer"), Some(&0));
+    /// # Ok::<(), hashmap_settings::account::BuildError<i32>>(())
+    /// ```
+    pub fn layer_at(mut self, index: usize, account: Self) -> Self {
+        self.accounts.insert(index, account);
+        self
+    }
+}
+impl<N, K: Eq + Hash, V> AccountBuilder<N, K, V> {
+    /// Adds a setting to the `Account`
+    ///
+    /// If the setting was already added, the value is replaced.
+    ///
+    /// If the `Account` has children containing this setting, their value will have priority
+    /// as the `Account` [settings](Account#settings) are updated by them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::AccountBuilder;
+    /// let account = AccountBuilder::<String,&str,i32>::new()
+    ///     .setting("answer", 0)
+    ///     .setting("answer", 42)
+    ///     .build()?;
+    /// assert_eq!(account.get(&"answer"), Some(&42));
+    /// # Ok::<(), hashmap_settings::account::BuildError<String>>(())
+    /// ```
+    pub fn setting(mut self, setting_name: K, setting_value: V) -> Self {
+        self.settings.insert(setting_name, setting_value);
+        self
+    }
+    /// Adds multiple settings to the `Account`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::AccountBuilder;
+    /// let account = AccountBuilder::<String,&str,i32>::new()
+    ///     .settings([("answer", 42), ("zero", 0)])
+    ///     .build()?;
+    /// assert_eq!(account.get(&"zero"), Some(&0));
+    /// # Ok::<(), hashmap_settings::account::BuildError<String>>(())
+    /// ```
+    pub fn settings<I: IntoIterator<Item = (K, V)>>(mut self, settings: I) -> Self {
+        self.settings.extend(settings);
+        self
+    }
+}
+impl<N: Clone + Eq + Hash + Incrementable, K: Clone + Eq + Hash, V: Clone + PartialEq>
+    AccountBuilder<N, K, V>
+{
+    /// Builds a [valid](Account#valid) `Account`
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BuildError::DuplicateName`] if any `Account` in the tree has
+    /// two children with the same name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::{AccountBuilder,BuildError};
+    /// let result = AccountBuilder::<i32,(),()>::new()
+    ///     .child(AccountBuilder::new().name(1))
+    ///     .child(AccountBuilder::new().name(1))
+    ///     .build();
+    /// assert_eq!(result, Err(BuildError::DuplicateName(1)));
+    /// ```
+    pub fn build(self) -> Result<Account<N, K, V>, BuildError<N>> {
+        let mut account = self.build_unchecked()?;
+        account.fix_valid(Valid::new_true());
+        Ok(account)
+    }
+    fn build_unchecked(self) -> Result<Account<N, K, V>, BuildError<N>> {
+        let mut names = HashSet::with_capacity(self.accounts.len());
+        for account in &self.accounts {
+            if !names.insert(&account.name) {
+                return Err(BuildError::DuplicateName(account.name.clone()));
+            }
+        }
+        let accounts = self
+            .accounts
+            .into_iter()
+            .map(Self::build_unchecked)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Account::new_unchecked(
+            self.name,
+            self.active,
+            self.settings,
+            accounts,
+            Valid::new(true, false, false),
+        ))
+    }
+}
+
+impl<N: Default, K, V> Default for AccountBuilder<N, K, V> {
+    fn default() -> Self {
+        Self {
+            name: N::default(),
+            active: true,
+            settings: HashMap::default(),
+            accounts: Vec::default(),
+        }
+    }
+}
+impl<N: Clone, K: Clone, V: Clone> Clone for AccountBuilder<N, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            active: self.active,
+            settings: self.settings.clone(),
+            accounts: self.accounts.clone(),
+        }
+    }
+}
+impl<N: core::fmt::Debug, K: core::fmt::Debug, V: core::fmt::Debug> core::fmt::Debug
+    for AccountBuilder<N, K, V>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AccountBuilder")
+            .field("name", &self.name)
+            .field("active", &self.active)
+            .field("settings", &self.settings)
+            .field("accounts", &self.accounts)
+            .finish()
+    }
+}
+/// Errors of [`AccountBuilder::build`]
+#[derive(Debug, PartialEq, Eq)]
+pub enum BuildError<N> {
+    /// Error of an `Account` having two children with the same name
+    DuplicateName(N),
+}
@@ -0,0 +1,602 @@
+use core::fmt::Debug;
+use std::{collections::HashMap, hash::Hash, sync::Arc};
+
+use crate::account::{Account, DeepError, DeepNames};
+
+type Predicate<K, V> = Arc<dyn Fn(&HashMap<K, V>) -> bool + Send + Sync>;
+
+/// A predicate that sets the activity of a child [`Account`]
+///
+/// The predicate is evaluated with the settings of the parent `Account`, and the child `Account`
+/// is [active](Account#active) while it returns `true`. It can also read a context captured by the closure,
+/// like the OS, the hostname, the time of day or a feature flag.
+///
+/// The settings the predicate reads have to be listed, as the condition is evaluated again whenever
+/// one of them is [updated](Account::update_setting) in the parent `Account`. Changes to the context
+/// require calling [`update_conditions`](Account::update_conditions).
+///
+/// Set with [`set_condition`](Account::set_condition) or [`deep_set_condition`](Account::deep_set_condition).
+///
+/// # Examples
+///
+/// ```
+/// use hashmap_settings::account::{Account,Condition,Valid};
+///
+/// let mut dark_theme = Account::<String,&str,String>::new(
+///     "Dark Theme".to_string(),
+///     false,
+///     [("background", "black".to_string())].into(),
+///     vec![],
+/// );
+/// dark_theme.set_condition(Some(Condition::equals("theme", "dark".to_string())));
+///
+/// let mut account = Account::new(
+///     "Main".to_string(),
+///     true,
+///     Default::default(),
+///     vec![Account::new(
+///         "Default".to_string(),
+///         true,
+///         [("theme", "light".to_string()), ("background", "white".to_string())].into(),
+///         vec![],
+///     )],
+/// );
+/// account.push(dark_theme, Valid::new_true());
+/// account.push(Account::new("User".to_string(), true, Default::default(), vec![]), Valid::new_true());
+/// assert_eq!(account.get(&"background"), Some(&"white".to_string()));
+///
+/// account.deep_insert(&"theme", "dark".to_string(), &mut vec![&"User".to_string()])?;
+/// assert_eq!(account.get(&"background"), Some(&"black".to_string()));
+/// # Ok::<(), hashmap_settings::account::DeepError>(())
+/// ```
+pub struct Condition<K, V> {
+    settings: Vec<K>,
+    predicate: Predicate<K, V>,
+}
+
+impl<K, V> Condition<K, V> {
+    /// Creates a new `Condition` from the settings it reads and the predicate
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::{Arc,atomic::{AtomicBool,Ordering}};
+    /// use hashmap_settings::account::{Account,Condition};
+    ///
+    /// let flag = Arc::new(AtomicBool::new(false));
+    /// let beta = Arc::clone(&flag);
+    /// let mut preview = Account::<String,&str,i32>::new("Preview".to_string(), false, [("lines", 5)].into(), vec![]);
+    /// preview.set_condition(Some(Condition::new(vec![], move |_| beta.load(Ordering::Relaxed))));
+    ///
+    /// let mut account = Account::new(
+    ///     "Main".to_string(),
+    ///     true,
+    ///     Default::default(),
+    ///     vec![Account::new("Default".to_string(), true, [("lines", 3)].into(), vec![]), preview],
+    /// );
+    /// assert_eq!(account.get(&"lines"), Some(&3));
+    ///
+    /// flag.store(true, Ordering::Relaxed);
+    /// account.update_conditions().unwrap();
+    /// assert_eq!(account.get(&"lines"), Some(&5));
+    /// ```
+    pub fn new<F: Fn(&HashMap<K, V>) -> bool + Send + Sync + 'static>(
+        settings: Vec<K>,
+        predicate: F,
+    ) -> Self {
+        Self {
+            settings,
+            predicate: Arc::new(predicate),
+        }
+    }
+    /// Returns the settings read by the `Condition`
+    #[must_use]
+    pub fn settings(&self) -> &[K] {
+        &self.settings
+    }
+    fn evaluate(&self, settings: &HashMap<K, V>) -> bool {
+        (self.predicate)(settings)
+    }
+}
+impl<K: Clone + Eq + Hash + Send + Sync + 'static, V: PartialEq + Send + Sync + 'static>
+    Condition<K, V>
+{
+    /// Creates a `Condition` that is met when a setting has the value provided
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::Condition;
+    ///
+    /// let condition = Condition::<&str,i32>::equals("lines", 3);
+    /// assert_eq!(condition.settings(), &["lines"]);
+    /// ```
+    pub fn equals(setting_name: K, value: V) -> Self {
+        let key = setting_name.clone();
+        Self::new(vec![setting_name], move |settings| {
+            settings.get(&key) == Some(&value)
+        })
+    }
+}
+impl<K: Clone, V> Clone for Condition<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            settings: self.settings.clone(),
+            predicate: Arc::clone(&self.predicate),
+        }
+    }
+}
+#[allow(clippy::missing_fields_in_debug)]
+impl<K: Debug, V> Debug for Condition<K, V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Condition")
+            .field("settings", &self.settings)
+            .finish()
+    }
+}
+
+/// Errors of the [conditions](Condition) of the child `Accounts` of an [`Account`]
+#[derive(Debug, PartialEq, Eq)]
+pub enum ConditionError<N> {
+    /// Error of providing the names of a child `Account` that doesn't exist, or an empty `Vec`
+    Deep(DeepError),
+    /// Error of conditions that depend on the activity of their own `Accounts`, with the names of the `Accounts` in the cycle
+    ///
+    /// A condition depends on the activity of an `Account` with a condition if that `Account`
+    /// contains one of the settings it reads.
+    Cycle(Vec<N>),
+}
+impl<N> From<DeepError> for ConditionError<N> {
+    fn from(error: DeepError) -> Self {
+        Self::Deep(error)
+    }
+}
+
+impl<N, K, V> Account<N, K, V> {
+    /// Returns the [`Condition`] that sets the activity of the `Account`, if it has one
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::{Account,Condition};
+    ///
+    /// let mut account = Account::<(),&str,i32>::default();
+    /// assert!(account.condition().is_none());
+    /// account.set_condition(Some(Condition::equals("lines", 3)));
+    /// assert_eq!(account.condition().unwrap().settings(), &["lines"]);
+    /// ```
+    #[must_use]
+    pub const fn condition(&self) -> Option<&Condition<K, V>> {
+        self.condition.as_ref()
+    }
+    /// Sets the [`Condition`] that sets the activity of the `Account`, returning the previous one
+    ///
+    /// The `Condition` is evaluated by the parent `Account`, once the `Account` is [pushed](Account::push) to it
+    /// or when calling [`update_conditions`](Account::update_conditions). Use [`deep_set_condition`](Account::deep_set_condition)
+    /// to set the `Condition` of a child `Account` and evaluate it right away.
+    ///
+    /// Removing the `Condition` keeps the activity the `Account` had.
+    ///
+    /// Conditions are cloned with the `Account` but aren't serialized, and are ignored when comparing `Accounts`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::{Account,Condition};
+    ///
+    /// let mut account = Account::<(),&str,i32>::default();
+    /// assert!(account.set_condition(Some(Condition::equals("lines", 3))).is_none());
+    /// assert!(account.set_condition(None).is_some());
+    /// ```
+    pub fn set_condition(&mut self, condition: Option<Condition<K, V>>) -> Option<Condition<K, V>> {
+        self.snapshot.clear();
+        core::mem::replace(&mut self.condition, condition)
+    }
+}
+impl<N, K: Eq + Hash, V> Account<N, K, V> {
+    /// Returns `true` if one of the conditions of the child `Accounts` reads the setting.
+    pub(super) fn conditions_read(&self, setting: &K) -> bool {
+        self.accounts.iter().any(|account| {
+            account
+                .condition
+                .as_ref()
+                .is_some_and(|condition| condition.settings.contains(setting))
+        })
+    }
+    /// Returns the indexes of the child `Accounts` with a condition, in the order the conditions can be evaluated,
+    /// or the indexes of the `Accounts` in a cycle.
+    fn condition_order(&self) -> Result<Vec<usize>, Vec<usize>> {
+        let conditional = (0..self.accounts.len())
+            .filter(|&index| self.accounts[index].condition.is_some())
+            .collect::<Vec<_>>();
+        let provided = conditional
+            .iter()
+            .map(|&index| self.accounts[index].layer_keys())
+            .collect::<Vec<_>>();
+        // the conditions each condition depends on
+        let dependencies = conditional
+            .iter()
+            .map(|&index| {
+                let read = self.accounts[index]
+                    .condition
+                    .as_ref()
+                    .map_or(&[][..], |condition| &condition.settings);
+                (0..conditional.len())
+                    .filter(|&other| provided[other].iter().any(|key| read.contains(key)))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let mut visited = vec![false; conditional.len()];
+        let mut order = vec![];
+        for condition in 0..conditional.len() {
+            visit(
+                condition,
+                &dependencies,
+                &mut visited,
+                &mut vec![],
+                &mut order,
+            )
+            .map_err(|cycle| {
+                cycle
+                    .into_iter()
+                    .map(|index| conditional[index])
+                    .collect::<Vec<_>>()
+            })?;
+        }
+        Ok(order.into_iter().map(|index| conditional[index]).collect())
+    }
+}
+/// Adds a condition to `order` after the conditions it depends on, returning the conditions in a cycle if one is found.
+fn visit(
+    condition: usize,
+    dependencies: &[Vec<usize>],
+    visited: &mut [bool],
+    path: &mut Vec<usize>,
+    order: &mut Vec<usize>,
+) -> Result<(), Vec<usize>> {
+    if let Some(start) = path.iter().position(|&index| index == condition) {
+        return Err(path[start..].to_vec());
+    }
+    if visited[condition] {
+        return Ok(());
+    }
+    path.push(condition);
+    for &dependency in &dependencies[condition] {
+        visit(dependency, dependencies, visited, path, order)?;
+    }
+    path.pop();
+    visited[condition] = true;
+    order.push(condition);
+    Ok(())
+}
+impl<N, K: Clone + Eq + Hash, V: Clone> Account<N, K, V> {
+    /// Evaluates the conditions of the child `Accounts`, updating the settings of the ones that changed activity.
+    ///
+    /// Returns the indexes of the `Accounts` in a cycle, in which case no condition is evaluated.
+    pub(super) fn evaluate_conditions(&mut self) -> Result<(), Vec<usize>> {
+        for index in self.condition_order()? {
+            let Some(condition) = &self.accounts[index].condition else {
+                continue;
+            };
+            let active = condition.evaluate(&self.settings);
+            if self.accounts[index].active == active {
+                continue;
+            }
+            self.accounts[index].active = active;
+            let keys = self.accounts[index]
+                .layer_keys()
+                .into_iter()
+                .cloned()
+                .collect::<Vec<_>>();
+            for key in keys {
+                self.refresh_setting(&key);
+                self.add_pending(key);
+            }
+        }
+        Ok(())
+    }
+    /// Updates the settings changed in the child `Accounts` by the evaluation of their conditions.
+    pub(super) fn update_pending(&mut self) {
+        let mut pending = vec![];
+        for account in &mut self.accounts {
+            pending.append(&mut account.pending);
+        }
+        let mut read = false;
+        for key in pending {
+            self.refresh_setting(&key);
+            read |= self.conditions_read(&key);
+            self.add_pending(key);
+        }
+        if read {
+            // cycles are returned by `update_conditions`
+            let _ = self.evaluate_conditions();
+        }
+    }
+    /// Marks a setting as changed by a condition, to be updated by the parent `Account`.
+    fn add_pending(&mut self, setting: K) {
+        if !self.pending.contains(&setting) {
+            self.pending.push(setting);
+        }
+    }
+}
+impl<N: Clone, K: Clone + Eq + Hash, V: Clone> Account<N, K, V> {
+    /// Evaluates the [conditions](Condition) of all the child `Accounts` and updates the settings
+    ///
+    /// Conditions are evaluated again when the settings they read are updated, but this is needed
+    /// when the context they read changes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConditionError::Cycle`] if the conditions of the child `Accounts` of an `Account` form a cycle.
+    /// Those conditions aren't evaluated, but the other `Accounts` are still updated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::{Account,Condition,ConditionError};
+    ///
+    /// let mut dark_theme = Account::<String,&str,&str>::new("Dark Theme".to_string(), true, [("theme", "dark")].into(), vec![]);
+    /// dark_theme.set_condition(Some(Condition::equals("theme", "dark")));
+    /// let mut account = Account::new("Main".to_string(), true, Default::default(), vec![dark_theme]);
+    ///
+    /// assert_eq!(account.update_conditions(), Err(ConditionError::Cycle(vec!["Dark Theme".to_string()])));
+    /// ```
+    pub fn update_conditions(&mut self) -> Result<(), ConditionError<N>> {
+        let mut result = Ok(());
+        for account in &mut self.accounts {
+            let updated = account.update_conditions();
+            if result.is_ok() {
+                result = updated;
+            }
+        }
+        self.update_pending();
+        if let Err(cycle) = self.evaluate_conditions()
+            && result.is_ok()
+        {
+            result = Err(ConditionError::Cycle(
+                cycle
+                    .into_iter()
+                    .map(|index| self.accounts[index].name.clone())
+                    .collect(),
+            ));
+        }
+        result
+    }
+}
+impl<N: Clone + PartialEq, K: Clone + Eq + Hash, V: Clone> Account<N, K, V> {
+    /// Sets the [`Condition`] of a child `Account`, returning the previous one
+    ///
+    /// Part of the [deep functions](Account#deep-functions) group that accept a `Vec` of &N to identify
+    /// the child `Account` to run the function. [`set_condition`](Account::set_condition) in this case.
+    ///
+    /// The `Condition` is evaluated right away, and all the affected accounts are updated such that they contain the correct values.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConditionError::Deep`] with the [`DeepError`] of the deep function, and
+    /// [`ConditionError::Cycle`] if the `Condition` would form a cycle, in which case it isn't set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::{Account,Condition,ConditionError};
+    ///
+    /// let mut account = Account::<String,&str,i32>::new(
+    ///     "Main".to_string(),
+    ///     true,
+    ///     Default::default(),
+    ///     vec![
+    ///         Account::new("Default".to_string(), true, [("lines", 3), ("mode", 1)].into(), vec![]),
+    ///         Account::new("Compact".to_string(), true, [("lines", 1)].into(), vec![]),
+    ///     ],
+    /// );
+    /// let compact = "Compact".to_string();
+    /// account.deep_set_condition(Some(Condition::equals("mode", 2)), &mut vec![&compact])?;
+    /// assert_eq!(account.get(&"lines"), Some(&3));
+    ///
+    /// assert_eq!(
+    ///     account.deep_set_condition(Some(Condition::equals("lines", 1)), &mut vec![&compact]).err(),
+    ///     Some(ConditionError::Cycle(vec![compact.clone()]))
+    /// );
+    /// # Ok::<(), ConditionError<String>>(())
+    /// ```
+    pub fn deep_set_condition<'a>(
+        &mut self,
+        condition: Option<Condition<K, V>>,
+        account_names: impl DeepNames<'a, N>,
+    ) -> Result<Option<Condition<K, V>>, ConditionError<N>>
+    where
+        N: 'a,
+    {
+        let account_names = &mut account_names.deep_names();
+        let Some(account_to_find) = account_names.pop() else {
+            return Err(DeepError::EmptyVec.into());
+        };
+        let Some(found_account) = self.mut_account_from_name(account_to_find) else {
+            return Err(DeepError::NotFound.into());
+        };
+        if !account_names.is_empty() {
+            let old = found_account.deep_set_condition(condition, account_names)?;
+            self.update_pending();
+            return Ok(old);
+        }
+        let old = found_account.set_condition(condition);
+        if let Err(cycle) = self.condition_order() {
+            let names = cycle
+                .into_iter()
+                .map(|index| self.accounts[index].name.clone())
+                .collect();
+            if let Some(found_account) = self.mut_account_from_name(account_to_find) {
+                found_account.set_condition(old);
+            }
+            return Err(ConditionError::Cycle(names));
+        }
+        // there's no cycle
+        let _ = self.evaluate_conditions();
+        Ok(old)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        sync::{
+            Arc,
+            atomic::{AtomicBool, Ordering},
+        },
+    };
+
+    use crate::account::{Account, Condition, ConditionError, Valid};
+
+    fn account() -> Account<String, &'static str, &'static str> {
+        Account::new(
+            "Main".to_string(),
+            true,
+            HashMap::new(),
+            vec![Account::new(
+                "Project".to_string(),
+                true,
+                HashMap::new(),
+                vec![
+                    Account::new(
+                        "Default".to_string(),
+                        true,
+                        [("theme", "light"), ("background", "white")].into(),
+                        vec![],
+                    ),
+                    Account::new(
+                        "Dark Theme".to_string(),
+                        true,
+                        [("background", "black"), ("font", "mono")].into(),
+                        vec![],
+                    ),
+                    Account::new(
+                        "Contrast".to_string(),
+                        true,
+                        [("border", "thick")].into(),
+                        vec![],
+                    ),
+                    Account::new("User".to_string(), true, HashMap::new(), vec![]),
+                ],
+            )],
+        )
+    }
+
+    #[test]
+    fn conditions_follow_settings() {
+        let mut account = account();
+        let (project, dark, contrast, user) = (
+            "Project".to_string(),
+            "Dark Theme".to_string(),
+            "Contrast".to_string(),
+            "User".to_string(),
+        );
+        account
+            .deep_set_condition(
+                Some(Condition::new(vec!["background"], |settings| {
+                    settings.get(&"background") == Some(&"black")
+                })),
+                &mut vec![&contrast, &project],
+            )
+            .unwrap();
+        account
+            .deep_set_condition(
+                Some(Condition::equals("theme", "dark")),
+                &mut vec![&dark, &project],
+            )
+            .unwrap();
+        assert_eq!(account.get(&"background"), Some(&"white"));
+        assert_eq!(account.get(&"border"), None);
+
+        // "Contrast" depends on "Dark Theme", so it's evaluated after it
+        account
+            .deep_insert(&"theme", "dark", &mut vec![&user, &project])
+            .unwrap();
+        assert_eq!(account.get(&"background"), Some(&"black"));
+        assert_eq!(account.get(&"border"), Some(&"thick"));
+        assert!(account.valid().is_valid());
+        let mut updated = account.clone();
+        updated.update_valid(Valid::new_false());
+        assert!(updated.valid().is_valid());
+
+        account
+            .deep_remove(&"theme", &mut vec![&user, &project])
+            .unwrap();
+        assert_eq!(account.get(&"font"), None);
+        assert_eq!(account.get(&"border"), None);
+        assert_eq!(account.snapshot().to_account(), account);
+
+        assert_eq!(
+            account
+                .deep_set_condition(
+                    Some(Condition::equals("border", "thick")),
+                    &mut vec![&dark, &project]
+                )
+                .err(),
+            Some(ConditionError::Cycle(vec![dark.clone(), contrast]))
+        );
+        assert_eq!(
+            account
+                .deep(&mut vec![&dark, &project])
+                .unwrap()
+                .condition()
+                .unwrap()
+                .settings(),
+            &["theme"]
+        );
+    }
+    #[test]
+    fn updated_settings_evaluate_conditions() {
+        let mut account = account();
+        let (project, dark) = ("Project".to_string(), "Dark Theme".to_string());
+        account
+            .deep_set_condition(
+                Some(Condition::equals("theme", "dark")),
+                &mut vec![&dark, &project],
+            )
+            .unwrap();
+        let project = account.get_mut_account(0).unwrap();
+        assert_eq!(project.get(&"background"), Some(&"white"));
+
+        project.get_mut_account(3).unwrap().insert("theme", "dark");
+        assert_eq!(project.update_setting_returns(&"theme"), Some(true));
+        assert_eq!(project.get(&"background"), Some(&"black"));
+        assert_eq!(project.get(&"font"), Some(&"mono"));
+    }
+    #[test]
+    fn conditions_follow_context() {
+        let mut account = account();
+        let (project, dark) = ("Project".to_string(), "Dark Theme".to_string());
+        let night = Arc::new(AtomicBool::new(false));
+        let is_night = Arc::clone(&night);
+        account
+            .deep_set_condition(
+                Some(Condition::new(vec![], move |_| {
+                    is_night.load(Ordering::Relaxed)
+                })),
+                &mut vec![&dark, &project],
+            )
+            .unwrap();
+        assert_eq!(account.get(&"background"), Some(&"white"));
+
+        night.store(true, Ordering::Relaxed);
+        assert_eq!(account.update_conditions(), Ok(()));
+        assert_eq!(account.get(&"background"), Some(&"black"));
+        assert_eq!(account.get(&"font"), Some(&"mono"));
+
+        let mut pushed = Account::new(
+            "Light Theme".to_string(),
+            true,
+            [("background", "grey")].into(),
+            vec![],
+        );
+        pushed.set_condition(Some(Condition::equals("theme", "light")));
+        account
+            .deep_push(pushed, Valid::new_true(), &mut vec![&project])
+            .map_or(Ok(()), Err)
+            .unwrap();
+        assert_eq!(account.get(&"background"), Some(&"grey"));
+        assert!(account.valid().is_valid());
+    }
+}
@@ -0,0 +1,676 @@
+use core::fmt::{self, Debug, Display};
+use std::hash::Hash;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::account::Account;
+
+/// A difference between two [`Account`] trees
+///
+/// The path of a `Change` contains the names of the child `Accounts`, from the direct child of
+/// the compared `Accounts` to the `Account` where the change happened. An empty path is the
+/// compared `Account` itself.
+///
+/// Changes to child `Accounts` have the path of their parent `Account`, except for
+/// [`ActivityChanged`](Change::ActivityChanged) which has the path of the child `Account` itself.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Change<N, K, V> {
+    /// A setting that was added
+    Added {
+        /// Path of the `Account`
+        path: Vec<N>,
+        /// Key of the setting
+        key: K,
+        /// Value of the setting
+        value: V,
+    },
+    /// A setting that was removed
+    Removed {
+        /// Path of the `Account`
+        path: Vec<N>,
+        /// Key of the setting
+        key: K,
+        /// Value of the setting before it was removed
+        value: V,
+    },
+    /// A setting whose value changed
+    Modified {
+        /// Path of the `Account`
+        path: Vec<N>,
+        /// Key of the setting
+        key: K,
+        /// Value before the change
+        old: V,
+        /// Value after the change
+        new: V,
+    },
+    /// A child `Account` that was added
+    ///
+    /// The settings and child `Accounts` of the added `Account` follow as their own `Changes`.
+    AccountAdded {
+        /// Path of the parent `Account`
+        path: Vec<N>,
+        /// Position of the child `Account`
+        index: usize,
+        /// Name of the child `Account`
+        name: N,
+        /// Activity of the child `Account`
+        active: bool,
+    },
+    /// A child `Account` that was removed
+    ///
+    /// The settings and child `Accounts` of the removed `Account` come before it as their own `Changes`.
+    AccountRemoved {
+        /// Path of the parent `Account`
+        path: Vec<N>,
+        /// Position the child `Account` had
+        index: usize,
+        /// Name of the child `Account`
+        name: N,
+    },
+    /// A child `Account` that was renamed
+    AccountRenamed {
+        /// Path of the parent `Account`
+        path: Vec<N>,
+        /// Name before the change
+        old: N,
+        /// Name after the change
+        new: N,
+    },
+    /// Child `Accounts` that changed order
+    AccountsReordered {
+        /// Path of the parent `Account`
+        path: Vec<N>,
+        /// Names of all the child `Accounts` in the new order
+        names: Vec<N>,
+    },
+    /// A child `Account` that was activated or deactivated
+    ActivityChanged {
+        /// Path of the child `Account`
+        path: Vec<N>,
+        /// Activity after the change
+        active: bool,
+    },
+}
+impl<N, K, V> Change<N, K, V> {
+    /// Returns the path of the `Account` where the change happened
+    #[must_use]
+    pub const fn path(&self) -> &Vec<N> {
+        match self {
+            Self::Added { path, .. }
+            | Self::Removed { path, .. }
+            | Self::Modified { path, .. }
+            | Self::AccountAdded { path, .. }
+            | Self::AccountRemoved { path, .. }
+            | Self::AccountRenamed { path, .. }
+            | Self::AccountsReordered { path, .. }
+            | Self::ActivityChanged { path, .. } => path,
+        }
+    }
+}
+impl<N, K, V> Change<N, K, V> {
+    pub(super) const fn path_mut(&mut self) -> &mut Vec<N> {
+        match self {
+            Self::Added { path, .. }
+            | Self::Removed { path, .. }
+            | Self::Modified { path, .. }
+            | Self::AccountAdded { path, .. }
+            | Self::AccountRemoved { path, .. }
+            | Self::AccountRenamed { path, .. }
+            | Self::AccountsReordered { path, .. }
+            | Self::ActivityChanged { path, .. } => path,
+        }
+    }
+}
+impl<N: Clone, K: Clone, V: Clone> Change<&N, &K, &V> {
+    /// Returns a `Change` that owns its contents, by cloning them
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::Change;
+    ///
+    /// let default = "Default".to_string();
+    /// let change = Change::Added { path: vec![&default], key: &"lines", value: &3 };
+    /// assert_eq!(
+    ///     change.cloned(),
+    ///     Change::Added { path: vec!["Default".to_string()], key: "lines", value: 3 }
+    /// );
+    /// ```
+    #[must_use]
+    pub fn cloned(self) -> Change<N, K, V> {
+        let path = |path: Vec<&N>| path.into_iter().cloned().collect();
+        match self {
+            Self::Added {
+                path: p,
+                key,
+                value,
+            } => Change::Added {
+                path: path(p),
+                key: key.clone(),
+                value: value.clone(),
+            },
+            Self::Removed {
+                path: p,
+                key,
+                value,
+            } => Change::Removed {
+                path: path(p),
+                key: key.clone(),
+                value: value.clone(),
+            },
+            Self::Modified {
+                path: p,
+                key,
+                old,
+                new,
+            } => Change::Modified {
+                path: path(p),
+                key: key.clone(),
+                old: old.clone(),
+                new: new.clone(),
+            },
+            Self::AccountAdded {
+                path: p,
+                index,
+                name,
+                active,
+            } => Change::AccountAdded {
+                path: path(p),
+                index,
+                name: name.clone(),
+                active,
+            },
+            Self::AccountRemoved {
+                path: p,
+                index,
+                name,
+            } => Change::AccountRemoved {
+                path: path(p),
+                index,
+                name: name.clone(),
+            },
+            Self::AccountRenamed { path: p, old, new } => Change::AccountRenamed {
+                path: path(p),
+                old: old.clone(),
+                new: new.clone(),
+            },
+            Self::AccountsReordered { path: p, names } => Change::AccountsReordered {
+                path: path(p),
+                names: names.into_iter().cloned().collect(),
+            },
+            Self::ActivityChanged { path: p, active } => Change::ActivityChanged {
+                path: path(p),
+                active,
+            },
+        }
+    }
+}
+impl<N: Debug, K: Debug, V: Debug> Display for Change<N, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} ", self.path())?;
+        match self {
+            Self::Added { key, value, .. } => write!(f, "+ {key:?}: {value:?}"),
+            Self::Removed { key, value, .. } => write!(f, "- {key:?}: {value:?}"),
+            Self::Modified { key, old, new, .. } => write!(f, "~ {key:?}: {old:?} -> {new:?}"),
+            Self::AccountAdded {
+                index,
+                name,
+                active,
+                ..
+            } => write!(
+                f,
+                "+ account {name:?} at {index}{}",
+                if *active { "" } else { " (inactive)" }
+            ),
+            Self::AccountRemoved { index, name, .. } => {
+                write!(f, "- account {name:?} at {index}")
+            }
+            Self::AccountRenamed { old, new, .. } => {
+                write!(f, "~ account {old:?} renamed to {new:?}")
+            }
+            Self::AccountsReordered { names, .. } => write!(f, "~ accounts reordered to {names:?}"),
+            Self::ActivityChanged { active, .. } => {
+                f.write_str(if *active { "activated" } else { "deactivated" })
+            }
+        }
+    }
+}
+
+/// The differences between two [`Account`] trees
+///
+/// Created by [`Account::diff`], it contains a [`Change`] for each difference, and can be
+/// displayed as a report with one line per `Change`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diff<'a, N, K, V> {
+    changes: Vec<Change<&'a N, &'a K, &'a V>>,
+}
+impl<'a, N, K, V> Diff<'a, N, K, V> {
+    /// Returns the [`Change`]s
+    #[must_use]
+    pub const fn changes(&self) -> &Vec<Change<&'a N, &'a K, &'a V>> {
+        &self.changes
+    }
+    /// Returns `true` if the `Accounts` are the same
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+impl<'a, N, K, V> IntoIterator for Diff<'a, N, K, V> {
+    type Item = Change<&'a N, &'a K, &'a V>;
+    type IntoIter = std::vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.changes.into_iter()
+    }
+}
+impl<N: Debug, K: Debug, V: Debug> Display for Diff<'_, N, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{change}")?;
+        }
+        Ok(())
+    }
+}
+
+impl<N: PartialEq, K: Clone + Eq + Hash, V: PartialEq> Account<N, K, V> {
+    /// Returns the differences between `self` and `other`
+    ///
+    /// `self` is considered the old version and `other` the new one.
+    ///
+    /// Child `Accounts` are matched by name. A child `Account` whose name isn't present in the other
+    /// tree is considered renamed if the other tree has an unmatched child `Account` in the same position,
+    /// and removed or added otherwise.
+    ///
+    /// Only the settings an `Account` defines are compared, meaning those that none of its active child `Accounts`
+    /// contain, as the others are reported on the child `Accounts`. The settings of the same `Account`
+    /// are in arbitrary order.
+    ///
+    /// `K` has to be [`Clone`], as finding the settings an `Account` defines depends on the settings
+    /// [locked](Account::lock) in its child `Accounts`, which each `Account` keeps a copy of.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::{account::{Account,Change},stg::{Setting,Stg}};
+    ///
+    /// let old = Account::<String,&str,Stg>::new(
+    ///     "Main".to_string(),
+    ///     true,
+    ///     Default::default(),
+    ///     vec![Account::new("Default".to_string(), true, [("lines", 3.stg())].into(), vec![])],
+    /// );
+    /// let mut new = old.clone();
+    /// new.deep_insert(&"lines", 5.stg(), &mut vec![&"Default".to_string()])?;
+    /// new.push(Account::new("Local".to_string(), false, Default::default(), vec![]), Default::default());
+    ///
+    /// let diff = old.diff(&new);
+    /// assert_eq!(diff.changes(), &vec![
+    ///     Change::AccountAdded { path: vec![], index: 1, name: &"Local".to_string(), active: false },
+    ///     Change::Modified { path: vec![&"Default".to_string()], key: &"lines", old: &3.stg(), new: &5.stg() },
+    /// ]);
+    /// assert_eq!(
+    ///     diff.to_string(),
+    ///     "[] + account \"Local\" at 1 (inactive)\n[\"Default\"] ~ \"lines\": Stg { value: 3 } -> Stg { value: 5 }\n"
+    /// );
+    /// # Ok::<(), hashmap_settings::account::DeepError>(())
+    /// ```
+    pub fn diff<'a>(&'a self, other: &'a Self) -> Diff<'a, N, K, V> {
+        Diff::new(self, other)
+    }
+}
+
+/// A tree of `Accounts` that can be compared, implemented by [`Account`] and by the nodes of a [`Snapshot`](crate::account::Snapshot).
+pub(super) trait Tree<N, K, V> {
+    fn name(&self) -> &N;
+    fn active(&self) -> bool;
+    fn children(&self) -> usize;
+    fn child(&self, index: usize) -> &Self;
+    /// Returns the value of a setting defined by the tree, meaning that none of its active children contain it.
+    fn defined(&self, key: &K) -> Option<&V>;
+    /// Returns the settings defined by the tree, in arbitrary order.
+    fn defined_settings<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a;
+}
+impl<N, K: Clone + Eq + Hash, V> Tree<N, K, V> for Account<N, K, V> {
+    fn name(&self) -> &N {
+        &self.name
+    }
+    fn active(&self) -> bool {
+        self.active
+    }
+    fn children(&self) -> usize {
+        self.accounts.len()
+    }
+    fn child(&self, index: usize) -> &Self {
+        &self.accounts[index]
+    }
+    fn defined(&self, key: &K) -> Option<&V> {
+        self.settings
+            .get(key)
+            .filter(|_| self.sub_account_containing(key).is_none())
+    }
+    fn defined_settings<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        self.settings
+            .iter()
+            .filter(|(key, _)| self.sub_account_containing(key).is_none())
+    }
+}
+
+impl<'a, N: PartialEq, K: Eq + Hash, V: PartialEq> Diff<'a, N, K, V> {
+    pub(super) fn new<T: Tree<N, K, V>>(old: &'a T, new: &'a T) -> Self {
+        let mut changes = vec![];
+        diff_helper(old, new, &mut vec![], &mut changes);
+        Self { changes }
+    }
+}
+fn diff_helper<'a, N: PartialEq, K: Eq + Hash, V: PartialEq, T: Tree<N, K, V>>(
+    old: &'a T,
+    new: &'a T,
+    path: &mut Vec<&'a N>,
+    changes: &mut Vec<Change<&'a N, &'a K, &'a V>>,
+) {
+    for (key, old_value) in old.defined_settings() {
+        match new.defined(key) {
+            Some(new_value) => {
+                if old_value != new_value {
+                    changes.push(Change::Modified {
+                        path: path.clone(),
+                        key,
+                        old: old_value,
+                        new: new_value,
+                    });
+                }
+            }
+            None => changes.push(Change::Removed {
+                path: path.clone(),
+                key,
+                value: old_value,
+            }),
+        }
+    }
+    for (key, value) in new.defined_settings() {
+        if old.defined(key).is_none() {
+            changes.push(Change::Added {
+                path: path.clone(),
+                key,
+                value,
+            });
+        }
+    }
+
+    // pairs of matching child `Accounts`, by position in `old` and in `new`
+    let mut pairs = vec![];
+    let mut old_matched = vec![false; old.children()];
+    let mut new_matched = vec![false; new.children()];
+    for (old_index, old_matched) in old_matched.iter_mut().enumerate() {
+        if let Some(new_index) = (0..new.children())
+            .find(|new_index| new.child(*new_index).name() == old.child(old_index).name())
+        {
+            pairs.push((old_index, new_index));
+            *old_matched = true;
+            new_matched[new_index] = true;
+        }
+    }
+    for index in 0..old.children().min(new.children()) {
+        if !old_matched[index] && !new_matched[index] {
+            pairs.push((index, index));
+            old_matched[index] = true;
+            new_matched[index] = true;
+            changes.push(Change::AccountRenamed {
+                path: path.clone(),
+                old: old.child(index).name(),
+                new: new.child(index).name(),
+            });
+        }
+    }
+    for index in (0..old.children()).rev() {
+        if !old_matched[index] {
+            removed_helper(old.child(index), index, path, changes);
+        }
+    }
+    for (index, matched) in new_matched.iter().enumerate() {
+        if !matched {
+            let account = new.child(index);
+            changes.push(Change::AccountAdded {
+                path: path.clone(),
+                index,
+                name: account.name(),
+                active: account.active(),
+            });
+            path.push(account.name());
+            added_helper(account, path, changes);
+            path.pop();
+        }
+    }
+    pairs.sort_unstable_by_key(|(_, new_index)| *new_index);
+    if pairs.windows(2).any(|pair| pair[0].0 > pair[1].0) {
+        changes.push(Change::AccountsReordered {
+            path: path.clone(),
+            names: (0..new.children())
+                .map(|index| new.child(index).name())
+                .collect(),
+        });
+    }
+    for (old_index, new_index) in pairs {
+        let (old, new) = (old.child(old_index), new.child(new_index));
+        path.push(new.name());
+        if old.active() != new.active() {
+            changes.push(Change::ActivityChanged {
+                path: path.clone(),
+                active: new.active(),
+            });
+        }
+        diff_helper(old, new, path, changes);
+        path.pop();
+    }
+}
+/// Adds the contents of an added `Account` as `Changes`.
+fn added_helper<'a, N, K, V, T: Tree<N, K, V>>(
+    account: &'a T,
+    path: &mut Vec<&'a N>,
+    changes: &mut Vec<Change<&'a N, &'a K, &'a V>>,
+) {
+    for (key, value) in account.defined_settings() {
+        changes.push(Change::Added {
+            path: path.clone(),
+            key,
+            value,
+        });
+    }
+    for index in 0..account.children() {
+        let child = account.child(index);
+        changes.push(Change::AccountAdded {
+            path: path.clone(),
+            index,
+            name: child.name(),
+            active: child.active(),
+        });
+        path.push(child.name());
+        added_helper(child, path, changes);
+        path.pop();
+    }
+}
+
+/// Adds the contents of a removed `Account` as `Changes`, followed by its removal.
+fn removed_helper<'a, N, K, V, T: Tree<N, K, V>>(
+    account: &'a T,
+    index: usize,
+    path: &mut Vec<&'a N>,
+    changes: &mut Vec<Change<&'a N, &'a K, &'a V>>,
+) {
+    path.push(account.name());
+    for (key, value) in account.defined_settings() {
+        changes.push(Change::Removed {
+            path: path.clone(),
+            key,
+            value,
+        });
+    }
+    for index in (0..account.children()).rev() {
+        removed_helper(account.child(index), index, path, changes);
+    }
+    path.pop();
+    changes.push(Change::AccountRemoved {
+        path: path.clone(),
+        index,
+        name: account.name(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::account::{Account, Change, Valid};
+
+    fn account() -> Account<String, &'static str, i32> {
+        Account::new(
+            "Main".to_string(),
+            true,
+            HashMap::from([("main", 0)]),
+            vec![
+                Account::new(
+                    "Default".to_string(),
+                    true,
+                    [("lines", 3), ("words", 1)].into(),
+                    vec![],
+                ),
+                Account::new(
+                    "Local".to_string(),
+                    true,
+                    HashMap::new(),
+                    vec![Account::new(
+                        "Project".to_string(),
+                        true,
+                        [("words", 2)].into(),
+                        vec![],
+                    )],
+                ),
+                Account::new("Old".to_string(), true, HashMap::new(), vec![]),
+                Account::new("Removed".to_string(), true, HashMap::new(), vec![]),
+            ],
+        )
+    }
+
+    #[test]
+    fn same() {
+        assert!(account().diff(&account()).is_empty());
+    }
+    #[test]
+    fn changes() {
+        let old = account();
+        let mut new = account();
+        new.insert("main", 1);
+        new.deep_remove(&"lines", &mut vec![&"Default".to_string()])
+            .unwrap();
+        new.deep_insert(
+            &"ratio",
+            5,
+            &mut vec![&"Project".to_string(), &"Local".to_string()],
+        )
+        .unwrap();
+        new.deep_change_activity(false, &mut vec![&"Local".to_string()])
+            .unwrap();
+        new.deep_rename(&"New".to_string(), &mut vec![&"Old".to_string()])
+            .unwrap();
+        new.push(
+            Account::new(
+                "Added".to_string(),
+                true,
+                HashMap::new(),
+                vec![Account::new(
+                    "Inner".to_string(),
+                    false,
+                    [("lines", 7)].into(),
+                    vec![],
+                )],
+            ),
+            Valid::new_true(),
+        );
+
+        assert_eq!(
+            old.diff(&new).changes(),
+            &vec![
+                Change::Modified {
+                    path: vec![],
+                    key: &"main",
+                    old: &0,
+                    new: &1
+                },
+                Change::AccountRenamed {
+                    path: vec![],
+                    old: &"Old".to_string(),
+                    new: &"New".to_string()
+                },
+                Change::AccountAdded {
+                    path: vec![],
+                    index: 4,
+                    name: &"Added".to_string(),
+                    active: true
+                },
+                Change::AccountAdded {
+                    path: vec![&"Added".to_string()],
+                    index: 0,
+                    name: &"Inner".to_string(),
+                    active: false
+                },
+                Change::Added {
+                    path: vec![&"Added".to_string(), &"Inner".to_string()],
+                    key: &"lines",
+                    value: &7
+                },
+                Change::Removed {
+                    path: vec![&"Default".to_string()],
+                    key: &"lines",
+                    value: &3
+                },
+                Change::ActivityChanged {
+                    path: vec![&"Local".to_string()],
+                    active: false
+                },
+                Change::Added {
+                    path: vec![&"Local".to_string(), &"Project".to_string()],
+                    key: &"ratio",
+                    value: &5
+                },
+            ]
+        );
+    }
+    #[test]
+    fn reordered() {
+        let old = Account::<i32, &str, i32>::new(
+            0,
+            true,
+            HashMap::new(),
+            vec![
+                Account::new(1, true, HashMap::new(), vec![]),
+                Account::new(2, true, HashMap::new(), vec![]),
+                Account::new(3, true, HashMap::new(), vec![]),
+            ],
+        );
+        let new = Account::<i32, &str, i32>::new(
+            0,
+            true,
+            HashMap::new(),
+            vec![
+                Account::new(2, true, HashMap::new(), vec![]),
+                Account::new(1, true, HashMap::new(), vec![]),
+            ],
+        );
+        let diff = old.diff(&new);
+        assert_eq!(
+            diff.to_string(),
+            "[] - account 3 at 2\n[] ~ accounts reordered to [2, 1]\n"
+        );
+    }
+}
@@ -0,0 +1,558 @@
+use core::fmt::Debug;
+use std::hash::Hash;
+
+use crate::account::{Account, DeepError, DeepNames, Incrementable, Valid, deep_path};
+
+/// Undo and redo for the changes of an [`Account`]
+///
+/// A `History` owns an `Account` and records each change made through it, so that it can be
+/// [undone](History::undo) and [redone](History::redo). Undoing a change restores the
+/// settings and the [`Valid`] of every `Account` it affected, leaving the `Account` exactly as it was before.
+///
+/// Making a new change after undoing discards the changes that could be redone.
+///
+/// # Examples
+///
+/// ```
+/// use hashmap_settings::account::{Account,History};
+///
+/// let mut history = History::new(Account::<String,&str,i32>::new(
+///     "Main".to_string(),
+///     true,
+///     Default::default(),
+///     vec![Account::new("Default".to_string(), true, [("lines", 3)].into(), vec![])],
+/// ));
+/// history.deep_insert(&"lines", 5, &mut vec![&"Default".to_string()])?;
+/// history.deep_change_activity(false, &mut vec![&"Default".to_string()])?;
+/// assert_eq!(history.account().get(&"lines"), None);
+///
+/// assert!(history.undo());
+/// assert_eq!(history.account().get(&"lines"), Some(&5));
+/// assert!(history.undo());
+/// assert_eq!(history.account().get(&"lines"), Some(&3));
+/// assert!(!history.undo());
+///
+/// assert!(history.redo());
+/// assert_eq!(history.account().get(&"lines"), Some(&5));
+/// # Ok::<(), hashmap_settings::account::DeepError>(())
+/// ```
+pub struct History<N, K, V> {
+    account: Account<N, K, V>,
+    undo: Vec<Record<N, K, V>>,
+    redo: Vec<Record<N, K, V>>,
+}
+
+/// A change made to the `Account` at `path`, along with what is needed to undo it.
+struct Record<N, K, V> {
+    path: Vec<N>,
+    command: Command<N, K, V>,
+    levels: Vec<Level<K, V>>,
+    restore: Restore<N, K, V>,
+}
+enum Command<N, K, V> {
+    Insert(K, V),
+    Remove(K),
+    Rename(N),
+    ChangeActivity(bool),
+    Push(Box<Account<N, K, V>>, Valid),
+    Pop(Valid),
+}
+/// The state, before the change, of an `Account` in the path of the change.
+struct Level<K, V> {
+    valid: Valid,
+    settings: Vec<(K, Option<V>)>,
+    /// The settings with a tombstone in the `Account`.
+    unset: Vec<K>,
+}
+/// Structural changes to be undone, besides restoring the [`Level`]s.
+enum Restore<N, K, V> {
+    Settings,
+    Names(Vec<N>),
+    Active(bool),
+    Pop,
+    Push(Box<Account<N, K, V>>),
+}
+
+impl<N, K, V> History<N, K, V> {
+    /// Creates a new `History` with no changes recorded
+    pub const fn new(account: Account<N, K, V>) -> Self {
+        Self {
+            account,
+            undo: Vec::new(),
+            redo: Vec::new(),
+        }
+    }
+    /// Returns a reference to the `Account`
+    pub const fn account(&self) -> &Account<N, K, V> {
+        &self.account
+    }
+    /// Returns the `Account`, discarding the recorded changes
+    pub fn into_account(self) -> Account<N, K, V> {
+        self.account
+    }
+    /// Returns `true` if there is a change that can be undone
+    #[must_use]
+    pub const fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+    /// Returns `true` if there is a change that can be redone
+    #[must_use]
+    pub const fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+    /// Discards all recorded changes
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+impl<N: Clone + Eq + Hash + Incrementable, K: Clone + Eq + Hash, V: Clone + PartialEq>
+    History<N, K, V>
+{
+    /// Inserts a key-value pair into the map of the `Account`
+    ///
+    /// Recorded version of [`Account::insert`].
+    pub fn insert(&mut self, setting_name: K, setting_value: V) -> Option<V> {
+        self.record(vec![], Command::Insert(setting_name, setting_value))
+            .ok()
+            .and_then(Record::old_value)
+    }
+    /// Removes a setting from the map of the `Account`
+    ///
+    /// Recorded version of [`Account::remove`].
+    pub fn remove(&mut self, setting_to_remove: &K) -> Option<V> {
+        self.record(vec![], Command::Remove(setting_to_remove.clone()))
+            .ok()
+            .and_then(Record::old_value)
+    }
+    /// Inserts a key-value pair into the map of a child `Account`
+    ///
+    /// Recorded version of [`Account::deep_insert`].
+    ///
+    /// # Errors
+    ///
+    /// Deep functions can return [`DeepError`]'s
+    pub fn deep_insert<'a>(
+        &mut self,
+        setting_name: &K,
+        setting_value: V,
+        account_names: impl DeepNames<'a, N>,
+    ) -> Result<Option<V>, DeepError>
+    where
+        N: 'a,
+    {
+        let path = deep_path(account_names.deep_names())?;
+        self.record(path, Command::Insert(setting_name.clone(), setting_value))
+            .map(Record::old_value)
+    }
+    /// Removes a setting from the map of a child `Account`
+    ///
+    /// Recorded version of [`Account::deep_remove`].
+    ///
+    /// # Errors
+    ///
+    /// Deep functions can return [`DeepError`]'s
+    pub fn deep_remove<'a>(
+        &mut self,
+        setting_to_remove: &K,
+        account_names: impl DeepNames<'a, N>,
+    ) -> Result<Option<V>, DeepError>
+    where
+        N: 'a,
+    {
+        let path = deep_path(account_names.deep_names())?;
+        self.record(path, Command::Remove(setting_to_remove.clone()))
+            .map(Record::old_value)
+    }
+    /// Renames a child `Account`, returning its old name
+    ///
+    /// Recorded version of [`Account::deep_rename`].
+    ///
+    /// # Errors
+    ///
+    /// Deep functions can return [`DeepError`]'s
+    pub fn deep_rename<'a>(
+        &mut self,
+        new_name: &N,
+        account_names: impl DeepNames<'a, N>,
+    ) -> Result<N, DeepError>
+    where
+        N: 'a,
+    {
+        let path = deep_path(account_names.deep_names())?;
+        self.record(path, Command::Rename(new_name.clone()))
+            .map(|record| record.path[0].clone())
+    }
+    /// Changes the activity of a child `Account`, returning `true` if it changed
+    ///
+    /// Recorded version of [`Account::deep_change_activity`].
+    ///
+    /// # Errors
+    ///
+    /// Deep functions can return [`DeepError`]'s
+    pub fn deep_change_activity<'a>(
+        &mut self,
+        new_active: bool,
+        account_names: impl DeepNames<'a, N>,
+    ) -> Result<bool, DeepError>
+    where
+        N: 'a,
+    {
+        let path = deep_path(account_names.deep_names())?;
+        self.record(path, Command::ChangeActivity(new_active))
+            .map(|record| matches!(record.restore, Restore::Active(active) if active != new_active))
+    }
+    /// Appends an `Account` to the child `Accounts`
+    ///
+    /// Recorded version of [`Account::push`].
+    pub fn push(&mut self, account: Account<N, K, V>, valid: Valid) {
+        // pushing to the `Account` itself can't fail
+        let _ = self.record(vec![], Command::Push(Box::new(account), valid));
+    }
+    /// Appends an `Account` to the child `Accounts` of a child `Account`
+    ///
+    /// Recorded version of [`Account::deep_push`].
+    ///
+    /// # Errors
+    ///
+    /// Deep functions can return [`DeepError`]'s
+    pub fn deep_push<'a>(
+        &mut self,
+        account: Account<N, K, V>,
+        valid: Valid,
+        account_names: impl DeepNames<'a, N>,
+    ) -> Result<(), DeepError>
+    where
+        N: 'a,
+    {
+        let path = deep_path(account_names.deep_names())?;
+        self.record(path, Command::Push(Box::new(account), valid))
+            .map(|_| ())
+    }
+    /// Removes the last child `Account` and returns it, or [`None`] if there are none
+    ///
+    /// Recorded version of [`Account::pop`].
+    pub fn pop(&mut self, valid: Valid) -> Option<Account<N, K, V>> {
+        self.record(vec![], Command::Pop(valid))
+            .ok()
+            .and_then(Record::popped)
+    }
+    /// Removes the last child `Account` of a child `Account` and returns it, or [`None`] if there are none
+    ///
+    /// Recorded version of [`Account::deep_pop`].
+    ///
+    /// # Errors
+    ///
+    /// Deep functions can return [`DeepError`]'s
+    pub fn deep_pop<'a>(
+        &mut self,
+        valid: Valid,
+        account_names: impl DeepNames<'a, N>,
+    ) -> Result<Option<Account<N, K, V>>, DeepError>
+    where
+        N: 'a,
+    {
+        let path = deep_path(account_names.deep_names())?;
+        self.record(path, Command::Pop(valid)).map(Record::popped)
+    }
+    /// Undoes the last change, returning `false` if there was no change to undo
+    pub fn undo(&mut self) -> bool {
+        let Some(record) = self.undo.pop() else {
+            return false;
+        };
+        let target = match record.restore {
+            Restore::Names(_) => &record.path[1..],
+            _ => &record.path[..],
+        };
+        // the paths of the records always exist as the `Account` can only be changed through `History`
+        if let Some(account) = self.account.walk_mut(target) {
+            match &record.restore {
+                Restore::Settings => {}
+                Restore::Names(names) => {
+                    for (account, name) in account.accounts.iter_mut().zip(names) {
+                        account.name = name.clone();
+                    }
+                }
+                Restore::Active(active) => account.active = *active,
+                Restore::Pop => {
+                    account.accounts.pop();
+                }
+                Restore::Push(popped) => account.accounts.push(popped.as_ref().clone()),
+            }
+        }
+        if matches!(
+            record.restore,
+            Restore::Active(_) | Restore::Pop | Restore::Push(_)
+        ) {
+            // the active child `Accounts` changed, so their locks are found again when needed
+            self.account.clear_locks_in_path(target);
+        }
+        restore(&mut self.account, target, &record.levels);
+        self.redo.push(record);
+        true
+    }
+    /// Redoes the last undone change, returning `false` if there was no change to redo
+    pub fn redo(&mut self) -> bool {
+        let Some(record) = self.redo.pop() else {
+            return false;
+        };
+        let redo = core::mem::take(&mut self.redo);
+        let redone = self.record(record.path, record.command).is_ok();
+        self.redo = redo;
+        redone
+    }
+
+    fn record(
+        &mut self,
+        path: Vec<N>,
+        command: Command<N, K, V>,
+    ) -> Result<&Record<N, K, V>, DeepError> {
+        let target = match command {
+            Command::Rename(_) => &path[1..],
+            _ => &path[..],
+        };
+        let accounts = self.account.walk(target)?;
+        let Some(account) = accounts.last() else {
+            unreachable!("the path always contains the `Account` itself")
+        };
+        let (keys, restore): (Vec<&K>, _) = match &command {
+            Command::Insert(key, _) | Command::Remove(key) => (vec![key], Restore::Settings),
+            Command::Rename(_) => {
+                if account.account_from_name(&path[0]).is_none() {
+                    return Err(DeepError::NotFound);
+                }
+                (
+                    vec![],
+                    Restore::Names(account.accounts_names().into_iter().cloned().collect()),
+                )
+            }
+            Command::ChangeActivity(_) => (account.layer_keys(), Restore::Active(account.active)),
+            Command::Push(pushed, _) => (pushed.layer_keys(), Restore::Pop),
+            Command::Pop(_) => account.accounts.last().map_or_else(
+                || (vec![], Restore::Settings),
+                |popped| (popped.layer_keys(), Restore::Push(Box::new(popped.clone()))),
+            ),
+        };
+        let levels = accounts
+            .iter()
+            .map(|account| Level {
+                valid: account.valid,
+                settings: keys
+                    .iter()
+                    .map(|key| ((*key).clone(), account.get(key).cloned()))
+                    .collect(),
+                unset: keys
+                    .iter()
+                    .filter(|key| account.unset_settings().contains(key))
+                    .map(|key| (*key).clone())
+                    .collect(),
+            })
+            .collect();
+        let mut names = path.iter().collect::<Vec<_>>();
+        let account = &mut self.account;
+        match &command {
+            Command::Insert(key, value) if names.is_empty() => {
+                account.insert(key.clone(), value.clone());
+            }
+            Command::Insert(key, value) => {
+                account.deep_insert(key, value.clone(), &mut names)?;
+            }
+            Command::Remove(key) if names.is_empty() => {
+                account.remove(key);
+            }
+            Command::Remove(key) => {
+                account.deep_remove(key, &mut names)?;
+            }
+            Command::Rename(name) => {
+                account.deep_rename(name, &mut names)?;
+            }
+            Command::ChangeActivity(active) => {
+                account.deep_change_activity(*active, &mut names)?;
+            }
+            Command::Push(pushed, valid) if names.is_empty() => {
+                account.push(pushed.as_ref().clone(), *valid);
+            }
+            Command::Push(pushed, valid) => {
+                if let Some(error) = account.deep_push(pushed.as_ref().clone(), *valid, &mut names)
+                {
+                    return Err(error);
+                }
+            }
+            Command::Pop(valid) if names.is_empty() => {
+                account.pop(*valid);
+            }
+            Command::Pop(valid) => {
+                account.deep_pop(*valid, &mut names)?;
+            }
+        }
+        self.redo.clear();
+        self.undo.push(Record {
+            path,
+            command,
+            levels,
+            restore,
+        });
+        Ok(&self.undo[self.undo.len() - 1])
+    }
+}
+
+impl<N: Clone, K, V: Clone> Record<N, K, V> {
+    fn old_value(&self) -> Option<V> {
+        self.levels
+            .last()
+            .and_then(|level| level.settings.first())
+            .and_then(|(_, value)| value.clone())
+    }
+    fn popped(&self) -> Option<Account<N, K, V>>
+    where
+        K: Clone,
+    {
+        match &self.restore {
+            Restore::Push(popped) => Some(popped.as_ref().clone()),
+            _ => None,
+        }
+    }
+}
+
+impl<N: Debug, K: Debug, V: Debug> Debug for History<N, K, V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("History")
+            .field("account", &self.account)
+            .field("undo", &self.undo.len())
+            .field("redo", &self.redo.len())
+            .finish()
+    }
+}
+
+/// Restores the [`Level`]s of the `Account` and every child `Account` in `path`.
+fn restore<N: PartialEq, K: Clone + Eq + Hash, V: Clone>(
+    account: &mut Account<N, K, V>,
+    path: &[N],
+    levels: &[Level<K, V>],
+) {
+    let Some((level, levels)) = levels.split_first() else {
+        return;
+    };
+    for (key, value) in &level.settings {
+        match value {
+            Some(value) => {
+                account
+                    .subscriptions
+                    .insert(&mut account.settings, key.clone(), value.clone());
+            }
+            None => {
+                account.subscriptions.remove(&mut account.settings, key);
+            }
+        }
+        if level.unset.contains(key) {
+            account.add_tombstone(key.clone());
+        } else {
+            account.remove_tombstone(key);
+        }
+    }
+    account.valid = level.valid;
+    if let Some((name, path)) = path.split_last()
+        && let Some(child) = account.mut_account_from_name(name)
+    {
+        restore(child, path, levels);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::account::{Account, DeepError, History, Valid};
+
+    fn account() -> Account<String, &'static str, i32> {
+        Account::new(
+            "Main".to_string(),
+            true,
+            HashMap::from([("main", 0)]),
+            vec![
+                Account::new("Default".to_string(), true, [("lines", 3)].into(), vec![]),
+                Account::new(
+                    "Local".to_string(),
+                    true,
+                    HashMap::new(),
+                    vec![
+                        Account::new("Project".to_string(), true, [("words", 2)].into(), vec![]),
+                        Account::new("Other".to_string(), false, [("lines", 1)].into(), vec![]),
+                    ],
+                ),
+            ],
+        )
+    }
+
+    #[test]
+    fn undo_and_redo_everything() {
+        let mut history = History::new(account());
+        let (local, project) = ("Local".to_string(), "Project".to_string());
+        let other = "Other".to_string();
+        let mut states = vec![history.account().clone()];
+
+        assert_eq!(history.insert("main", 1), Some(0));
+        states.push(history.account().clone());
+        assert_eq!(history.remove(&"main"), Some(1));
+        states.push(history.account().clone());
+        let result = history.deep_insert(&"lines", 5, &mut vec![&project, &local]);
+        assert_eq!(result, Ok(None));
+        states.push(history.account().clone());
+        let result = history.deep_remove(&"words", &mut vec![&project, &local]);
+        assert_eq!(result, Ok(Some(2)));
+        states.push(history.account().clone());
+        let result = history.deep_change_activity(true, &mut vec![&other, &local]);
+        assert_eq!(result, Ok(true));
+        states.push(history.account().clone());
+        // renaming to an existing name increments the name of the sibling
+        let result = history.deep_rename(&project, &mut vec![&other, &local]);
+        assert_eq!(result, Ok(other));
+        states.push(history.account().clone());
+        // pushing an existing name increments the name of the pushed `Account`
+        let pushed = Account::new(local.clone(), true, [("lines", 7)].into(), vec![]);
+        history.push(pushed, Valid::new_true());
+        states.push(history.account().clone());
+        let pushed = Account::new(project.clone(), true, [("words", 9)].into(), vec![]);
+        let result = history.deep_push(pushed, Valid::new_true(), &mut vec![&local]);
+        assert_eq!(result, Ok(()));
+        states.push(history.account().clone());
+        let result = history.deep_pop(Valid::new_true(), &mut vec![&local]);
+        assert!(result.unwrap().is_some());
+        states.push(history.account().clone());
+        assert!(history.pop(Valid::new_true()).is_some());
+        states.push(history.account().clone());
+
+        for state in states.iter().rev().skip(1) {
+            assert!(history.undo());
+            assert!(history.account() == state);
+        }
+        assert!(!history.undo());
+        for state in states.iter().skip(1) {
+            assert!(history.redo());
+            assert!(history.account() == state);
+        }
+        assert!(!history.redo());
+    }
+    #[test]
+    fn errors_and_redo_stack() {
+        let mut history = History::new(account());
+        let missing = "Missing".to_string();
+        assert_eq!(
+            history.deep_insert(&"lines", 1, &mut vec![&missing]),
+            Err(DeepError::NotFound)
+        );
+        assert_eq!(
+            history.deep_remove(&"lines", &mut vec![]),
+            Err(DeepError::EmptyVec)
+        );
+        assert!(!history.can_undo());
+
+        history.insert("main", 1);
+        history.undo();
+        assert!(history.can_redo());
+        history.insert("main", 2);
+        assert!(!history.can_redo());
+        assert_eq!(history.account().get(&"main"), Some(&2));
+    }
+}
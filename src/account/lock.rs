@@ -0,0 +1,474 @@
+use std::{collections::HashSet, hash::Hash, sync::OnceLock};
+
+use crate::account::{Account, DeepError, DeepNames};
+
+/// The settings locked in an `Account` or in any of its active child `Accounts`, built when first needed.
+///
+/// Kept up to date for a setting whenever the `Account` [updates](Account::update_setting) it,
+/// and cleared when the child `Accounts` are added or removed.
+pub(super) struct LockCache<K> {
+    locks: OnceLock<HashSet<K>>,
+}
+impl<K> LockCache<K> {
+    pub(super) const fn new() -> Self {
+        Self {
+            locks: OnceLock::new(),
+        }
+    }
+    pub(super) fn clear(&mut self) {
+        self.locks.take();
+    }
+}
+impl<K> Default for LockCache<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N, K: Clone + Eq + Hash, V> Account<N, K, V> {
+    /// Locks a setting, returning `true` if it wasn't locked
+    ///
+    /// A locked setting can't be overridden by the `Accounts` above this one: when the `Account` is a
+    /// child `Account`, its parent ignores the values of the setting in the child `Accounts` above it,
+    /// even though they have a higher priority. This lets a lower layer, like a "Policy" `Account`,
+    /// enforce the value of a setting.
+    ///
+    /// The setting is locked even if the `Account` has no value for it, leaving it to the `Accounts` below.
+    ///
+    /// Use [`deep_lock`](Account::deep_lock) to lock a setting in a child `Account`, updating the parent `Accounts`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::{Account,Valid};
+    ///
+    /// let mut policy = Account::<String,&str,bool>::new("Policy".to_string(), true, [("telemetry", false)].into(), vec![]);
+    /// assert!(policy.lock("telemetry"));
+    /// assert!(!policy.lock("telemetry"));
+    ///
+    /// let mut account = Account::new("Main".to_string(), true, Default::default(), vec![policy]);
+    /// account.push(
+    ///     Account::new("User".to_string(), true, [("telemetry", true)].into(), vec![]),
+    ///     Valid::new_true(),
+    /// );
+    /// assert_eq!(account.get(&"telemetry"), Some(&false));
+    /// ```
+    pub fn lock(&mut self, setting_name: K) -> bool {
+        if self.locked.contains(&setting_name) {
+            return false;
+        }
+        if let Some(locks) = self.locks.locks.get_mut() {
+            locks.insert(setting_name.clone());
+        }
+        self.locked.push(setting_name);
+        self.snapshot.clear();
+        true
+    }
+    /// Unlocks a setting, returning `true` if it was locked
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::Account;
+    ///
+    /// let mut account = Account::<(),&str,bool>::default();
+    /// account.lock("telemetry");
+    /// assert!(account.unlock(&"telemetry"));
+    /// assert!(!account.unlock(&"telemetry"));
+    /// ```
+    pub fn unlock(&mut self, setting_name: &K) -> bool {
+        let Some(index) = self.locked.iter().position(|locked| locked == setting_name) else {
+            return false;
+        };
+        self.locked.remove(index);
+        self.refresh_lock(setting_name);
+        self.snapshot.clear();
+        true
+    }
+    /// Returns `true` if the setting is locked in the `Account` or in one of its active child `Accounts`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::Account;
+    ///
+    /// let mut policy = Account::<String,&str,bool>::new("Policy".to_string(), true, [("telemetry", false)].into(), vec![]);
+    /// policy.lock("telemetry");
+    /// let account = Account::new("Main".to_string(), true, Default::default(), vec![policy]);
+    ///
+    /// assert!(account.is_locked(&"telemetry"));
+    /// assert!(!account.is_locked(&"theme"));
+    /// ```
+    #[must_use]
+    pub fn is_locked(&self, setting_name: &K) -> bool {
+        self.subtree_locks().contains(setting_name)
+    }
+    /// Returns the settings locked in the `Account` itself, in the order they were locked
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::Account;
+    ///
+    /// let mut account = Account::<(),&str,bool>::default();
+    /// account.lock("telemetry");
+    /// account.lock("updates");
+    /// assert_eq!(account.locked_settings(), &["telemetry", "updates"]);
+    /// ```
+    #[must_use]
+    pub fn locked_settings(&self) -> &[K] {
+        &self.locked
+    }
+    /// Returns the settings locked in the `Account` or in any of its active child `Accounts`.
+    pub(super) fn subtree_locks(&self) -> &HashSet<K> {
+        self.locks.locks.get_or_init(|| {
+            let mut locks = self.locked.iter().cloned().collect::<HashSet<_>>();
+            for account in self.accounts.iter().filter(|account| account.active) {
+                locks.extend(account.subtree_locks().iter().cloned());
+            }
+            locks
+        })
+    }
+    /// Updates the cached locks of a setting, after it was locked or unlocked in the `Account` or its child `Accounts`.
+    pub(super) fn refresh_lock(&mut self, setting: &K) {
+        let locked = self.locked.contains(setting)
+            || self
+                .accounts
+                .iter()
+                .any(|account| account.active && account.is_locked(setting));
+        if let Some(locks) = self.locks.locks.get_mut() {
+            if locked {
+                locks.insert(setting.clone());
+            } else {
+                locks.remove(setting);
+            }
+        }
+    }
+    /// Clears the cached locks of the `Account` and of every child `Account` in `path`.
+    pub(super) fn clear_locks_in_path<M: PartialEq>(&mut self, path: &[M])
+    where
+        N: PartialEq<M>,
+    {
+        self.locks.clear();
+        if let Some((name, path)) = path.split_last()
+            && let Some(account) = self
+                .accounts
+                .iter_mut()
+                .find(|account| &account.name == name)
+        {
+            account.clear_locks_in_path(path);
+        }
+    }
+    /// Returns the active child `Accounts` the value of a setting can be taken from, from the lowest to the highest.
+    ///
+    /// The child `Accounts` above the lowest one where the setting is locked are left out.
+    pub(super) fn layers(&self, setting: &K) -> impl DoubleEndedIterator<Item = &Self> {
+        let end = self
+            .accounts
+            .iter()
+            .position(|account| account.active && account.is_locked(setting))
+            .map_or(self.accounts.len(), |locked| locked + 1);
+        self.accounts[..end].iter().filter(|account| account.active)
+    }
+}
+impl<N: PartialEq, K: Clone + Eq + Hash, V> Account<N, K, V> {
+    /// Returns `true` if the setting is locked in an active child `Account` below the one named `name`,
+    /// so the values of the setting in that `Account` are ignored.
+    pub(super) fn locked_below(&self, name: &N, setting: &K) -> bool {
+        self.accounts
+            .iter()
+            .take_while(|account| &account.name != name)
+            .any(|account| account.active && account.is_locked(setting))
+    }
+    /// Returns `true` if the setting is locked below any of the `Accounts` in `path`,
+    /// which goes from the deepest `Account` to the direct child.
+    pub(super) fn locked_in_path(&self, path: &[N], setting: &K) -> bool {
+        let Some((name, path)) = path.split_last() else {
+            return false;
+        };
+        self.locked_below(name, setting)
+            || self
+                .account_from_name(name)
+                .is_some_and(|account| account.locked_in_path(path, setting))
+    }
+}
+impl<N: PartialEq, K: Clone + Eq + Hash, V: Clone> Account<N, K, V> {
+    /// Locks a setting in a child `Account`, returning `true` if it wasn't locked
+    ///
+    /// Part of the [deep functions](Account#deep-functions) group that accept a `Vec` of &N to identify
+    /// the child `Account` to run the function. [`lock`](Account::lock) in this case.
+    ///
+    /// Also updates the setting in all the affected accounts such that they contain the correct values.
+    ///
+    /// # Errors
+    ///
+    /// Deep functions can return [`DeepError`]'s
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::{Account,DeepError};
+    ///
+    /// let mut account = Account::<String,&str,bool>::new(
+    ///     "Main".to_string(),
+    ///     true,
+    ///     Default::default(),
+    ///     vec![
+    ///         Account::new("Policy".to_string(), true, [("telemetry", false)].into(), vec![]),
+    ///         Account::new("User".to_string(), true, [("telemetry", true)].into(), vec![]),
+    ///     ],
+    /// );
+    /// assert_eq!(account.get(&"telemetry"), Some(&true));
+    ///
+    /// assert_eq!(account.deep_lock(&"telemetry", &mut vec![&"Policy".to_string()]), Ok(true));
+    /// assert_eq!(account.get(&"telemetry"), Some(&false));
+    /// assert_eq!(
+    ///     account.deep_insert(&"telemetry", true, &mut vec![&"User".to_string()]),
+    ///     Err(DeepError::Locked)
+    /// );
+    /// # Ok::<(), DeepError>(())
+    /// ```
+    pub fn deep_lock<'a>(
+        &mut self,
+        setting_name: &K,
+        account_names: impl DeepNames<'a, N>,
+    ) -> Result<bool, DeepError>
+    where
+        N: 'a,
+    {
+        let account_names = &mut account_names.deep_names();
+        let Some(account_to_find) = account_names.pop() else {
+            return Err(DeepError::EmptyVec);
+        };
+        let Some(found_account) = self.mut_account_from_name(account_to_find) else {
+            return Err(DeepError::NotFound);
+        };
+        let locked = if account_names.is_empty() {
+            found_account.lock(setting_name.clone())
+        } else {
+            found_account.deep_lock(setting_name, account_names)?
+        };
+        self.update_setting(setting_name);
+        Ok(locked)
+    }
+    /// Unlocks a setting in a child `Account`, returning `true` if it was locked
+    ///
+    /// Part of the [deep functions](Account#deep-functions) group that accept a `Vec` of &N to identify
+    /// the child `Account` to run the function. [`unlock`](Account::unlock) in this case.
+    ///
+    /// Also updates the setting in all the affected accounts such that they contain the correct values.
+    ///
+    /// # Errors
+    ///
+    /// Deep functions can return [`DeepError`]'s
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::Account;
+    ///
+    /// let mut policy = Account::<String,&str,bool>::new("Policy".to_string(), true, [("telemetry", false)].into(), vec![]);
+    /// policy.lock("telemetry");
+    /// let mut account = Account::new(
+    ///     "Main".to_string(),
+    ///     true,
+    ///     Default::default(),
+    ///     vec![policy, Account::new("User".to_string(), true, [("telemetry", true)].into(), vec![])],
+    /// );
+    /// assert_eq!(account.get(&"telemetry"), Some(&false));
+    ///
+    /// assert_eq!(account.deep_unlock(&"telemetry", &mut vec![&"Policy".to_string()]), Ok(true));
+    /// assert_eq!(account.get(&"telemetry"), Some(&true));
+    /// ```
+    pub fn deep_unlock<'a>(
+        &mut self,
+        setting_name: &K,
+        account_names: impl DeepNames<'a, N>,
+    ) -> Result<bool, DeepError>
+    where
+        N: 'a,
+    {
+        let account_names = &mut account_names.deep_names();
+        let Some(account_to_find) = account_names.pop() else {
+            return Err(DeepError::EmptyVec);
+        };
+        let Some(found_account) = self.mut_account_from_name(account_to_find) else {
+            return Err(DeepError::NotFound);
+        };
+        let unlocked = if account_names.is_empty() {
+            found_account.unlock(setting_name)
+        } else {
+            found_account.deep_unlock(setting_name, account_names)?
+        };
+        self.update_setting(setting_name);
+        Ok(unlocked)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::account::{Account, DeepError, MergePolicy, Valid};
+
+    fn account() -> Account<String, &'static str, Vec<i32>> {
+        let mut policy = Account::new(
+            "Policy".to_string(),
+            true,
+            [("lines", vec![3])].into(),
+            vec![],
+        );
+        policy.lock("lines");
+        Account::new(
+            "Main".to_string(),
+            true,
+            HashMap::new(),
+            vec![
+                Account::new(
+                    "Default".to_string(),
+                    true,
+                    [("lines", vec![1])].into(),
+                    vec![],
+                ),
+                Account::new("System".to_string(), true, HashMap::new(), vec![policy]),
+                Account::new(
+                    "User".to_string(),
+                    true,
+                    [("lines", vec![5]), ("words", vec![2])].into(),
+                    vec![],
+                ),
+            ],
+        )
+    }
+
+    #[test]
+    fn locks_ignore_higher_layers() {
+        let mut account = account();
+        let (system, policy, user) = (
+            "System".to_string(),
+            "Policy".to_string(),
+            "User".to_string(),
+        );
+        assert_eq!(account.get(&"lines"), Some(&vec![3]));
+        assert_eq!(account.get(&"words"), Some(&vec![2]));
+        assert_eq!(
+            account.get_with_origin(&"lines"),
+            Some((&vec![3], vec![system.clone(), policy.clone()]))
+        );
+
+        assert_eq!(
+            account.deep_insert(&"lines", vec![6], &mut vec![&user]),
+            Err(DeepError::Locked)
+        );
+        assert_eq!(
+            account.deep_unset(&"lines", &mut vec![&user]),
+            Err(DeepError::Locked)
+        );
+        assert_eq!(
+            account.deep(&mut vec![&user]).unwrap().get(&"lines"),
+            Some(&vec![5])
+        );
+        assert_eq!(
+            account.deep_insert(&"lines", vec![4], &mut vec![&policy, &system]),
+            Ok(Some(vec![3]))
+        );
+        assert_eq!(account.get(&"lines"), Some(&vec![4]));
+
+        // without a value the setting is taken from the `Accounts` below
+        account
+            .deep_remove(&"lines", &mut vec![&policy, &system])
+            .unwrap();
+        assert_eq!(account.get(&"lines"), Some(&vec![1]));
+
+        account
+            .deep_change_activity(false, &mut vec![&system])
+            .unwrap();
+        assert_eq!(account.get(&"lines"), Some(&vec![5]));
+        assert_eq!(
+            account.deep_insert(&"lines", vec![6], &mut vec![&user]),
+            Ok(Some(vec![5]))
+        );
+        account
+            .deep_change_activity(true, &mut vec![&system])
+            .unwrap();
+        assert_eq!(account.get(&"lines"), Some(&vec![1]));
+
+        account
+            .deep_unlock(&"lines", &mut vec![&policy, &system])
+            .unwrap();
+        assert_eq!(account.get(&"lines"), Some(&vec![6]));
+        assert!(account.valid().is_valid());
+    }
+    #[test]
+    fn locks_in_pushed_and_merged_settings() {
+        let mut account = account();
+        account.set_merge_policy(&"lines", MergePolicy::Append);
+        assert_eq!(account.get(&"lines"), Some(&vec![1, 3]));
+
+        account.push(
+            Account::new(
+                "Local".to_string(),
+                true,
+                [("lines", vec![7])].into(),
+                vec![],
+            ),
+            Valid::new_true(),
+        );
+        assert_eq!(account.get(&"lines"), Some(&vec![1, 3]));
+        let snapshot = account.snapshot();
+        assert_eq!(snapshot.get(&"lines"), Some(&vec![1, 3]));
+        assert!(snapshot.is_locked(&"lines"));
+        assert_eq!(snapshot.to_account(), account);
+
+        account.set_merge_policy(&"lines", MergePolicy::Override);
+        assert_eq!(account.get(&"lines"), Some(&vec![3]));
+        let mut updated = account.clone();
+        updated.update_valid(Valid::new_false());
+        assert!(updated.valid().is_valid());
+    }
+    #[test]
+    fn cached_locks_follow_changes() {
+        // clones find their locks again, so they are compared with the ones kept by `account`
+        fn assert_locks(
+            account: &Account<String, &'static str, Vec<i32>>,
+            lines: bool,
+            words: bool,
+        ) {
+            let clone = account.clone();
+            for (setting, locked) in [("lines", lines), ("words", words)] {
+                assert_eq!(account.is_locked(&setting), locked);
+                assert_eq!(clone.is_locked(&setting), locked);
+                assert_eq!(account.snapshot().is_locked(&setting), locked);
+            }
+        }
+        let mut account = account();
+        let (system, policy) = ("System".to_string(), "Policy".to_string());
+        assert_locks(&account, true, false);
+
+        account
+            .deep_unlock(&"lines", &mut vec![&policy, &system])
+            .unwrap();
+        assert_locks(&account, false, false);
+        account
+            .deep_lock(&"words", &mut vec![&policy, &system])
+            .unwrap();
+        assert_locks(&account, false, true);
+        account
+            .deep_change_activity(false, &mut vec![&policy, &system])
+            .unwrap();
+        assert_locks(&account, false, false);
+        account
+            .deep_change_activity(true, &mut vec![&policy, &system])
+            .unwrap();
+        assert_locks(&account, false, true);
+
+        let mut local = Account::new("Local".to_string(), true, HashMap::new(), vec![]);
+        local.lock("lines");
+        account.push(local, Valid::new_true());
+        assert_locks(&account, true, true);
+        account.pop(Valid::new_true());
+        assert_locks(&account, false, true);
+        account.remove_account(&system, Valid::new_true());
+        assert_locks(&account, false, false);
+    }
+}
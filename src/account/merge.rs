@@ -0,0 +1,443 @@
+use std::{collections::HashMap, hash::Hash};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{account::Account, stg::Stg};
+
+/// How the values of a setting in the child `Accounts` are combined by the parent `Account`
+///
+/// The values are merged from the lowest active child `Account` to the highest, so with `Override`
+/// the highest one wins, which is the default for every setting.
+///
+/// Set with [`Account::set_merge_policy`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum MergePolicy {
+    /// The value of the higher `Account` replaces the lower one
+    #[default]
+    Override,
+    /// Lists of higher `Accounts` are appended after the ones of the lower `Accounts`
+    Append,
+    /// Lists of higher `Accounts` are inserted before the ones of the lower `Accounts`
+    Prepend,
+    /// Like `Append`, but elements already present are skipped
+    Union,
+    /// Maps are merged recursively, with the values of the higher `Accounts` replacing the lower ones
+    DeepMerge,
+}
+
+/// Values that can be combined with a [`MergePolicy`]
+///
+/// Implemented for [`Stg`] holding a `Vec<Stg>` or a `HashMap<String,Stg>`, for [`Vec`] and for [`HashMap`].
+/// Values that the policy doesn't apply to are overridden.
+pub trait Merge: Clone {
+    /// Merges `other`, the value of a higher `Account`, into `self`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::{Merge,MergePolicy};
+    ///
+    /// let mut paths = vec!["/usr/bin", "/bin"];
+    /// paths.merge(&vec!["~/bin", "/bin"], MergePolicy::Union);
+    /// assert_eq!(paths, vec!["/usr/bin", "/bin", "~/bin"]);
+    /// ```
+    fn merge(&mut self, other: &Self, policy: MergePolicy);
+}
+impl<T: Clone + PartialEq> Merge for Vec<T> {
+    fn merge(&mut self, other: &Self, policy: MergePolicy) {
+        match policy {
+            MergePolicy::Override | MergePolicy::DeepMerge => self.clone_from(other),
+            MergePolicy::Append => self.extend_from_slice(other),
+            MergePolicy::Prepend => {
+                self.splice(0..0, other.iter().cloned());
+            }
+            MergePolicy::Union => {
+                for value in other {
+                    if !self.contains(value) {
+                        self.push(value.clone());
+                    }
+                }
+            }
+        }
+    }
+}
+#[allow(clippy::implicit_hasher)]
+impl<K: Clone + Eq + Hash, V: Merge> Merge for HashMap<K, V> {
+    fn merge(&mut self, other: &Self, policy: MergePolicy) {
+        if policy != MergePolicy::DeepMerge {
+            self.clone_from(other);
+            return;
+        }
+        for (key, value) in other {
+            match self.get_mut(key) {
+                Some(current) => current.merge(value, policy),
+                None => {
+                    self.insert(key.clone(), value.clone());
+                }
+            }
+        }
+    }
+}
+impl Merge for Stg {
+    fn merge(&mut self, other: &Self, policy: MergePolicy) {
+        if let (Some(list), Some(other)) = (
+            self.downcast_mut::<Vec<Self>>(),
+            other.downcast_ref::<Vec<Self>>(),
+        ) {
+            list.merge(other, policy);
+        } else if let (Some(map), Some(other)) = (
+            self.downcast_mut::<HashMap<String, Self>>(),
+            other.downcast_ref::<HashMap<String, Self>>(),
+        ) {
+            map.merge(other, policy);
+        } else {
+            self.clone_from(other);
+        }
+    }
+}
+
+type MergeFn<V> = fn(&[&V], MergePolicy) -> Option<V>;
+
+/// The settings of an `Account` whose values aren't simply taken from the highest child `Account`.
+///
+/// The function merging the values is kept alongside the policies, so they can be merged
+/// by methods that don't require `V` to implement [`Merge`].
+///
+/// Few settings have a policy, so they are kept in a `Vec`.
+pub(super) struct MergePolicies<K, V> {
+    policies: Vec<(K, MergePolicy)>,
+    merge: Option<MergeFn<V>>,
+}
+impl<K, V> MergePolicies<K, V> {
+    pub(super) const fn new() -> Self {
+        Self {
+            policies: Vec::new(),
+            merge: None,
+        }
+    }
+    pub(super) const fn is_empty(&self) -> bool {
+        self.policies.is_empty()
+    }
+}
+impl<K: Eq, V> MergePolicies<K, V> {
+    pub(super) fn get(&self, setting: &K) -> Option<MergePolicy> {
+        self.policies
+            .iter()
+            .find(|(key, _)| key == setting)
+            .map(|(_, policy)| *policy)
+    }
+    pub(super) fn contains(&self, setting: &K) -> bool {
+        self.get(setting).is_some()
+    }
+    fn set(&mut self, setting: K, policy: MergePolicy) {
+        self.policies.retain(|(key, _)| *key != setting);
+        if policy != MergePolicy::Override {
+            self.policies.push((setting, policy));
+        }
+    }
+}
+impl<K: Clone, V> Clone for MergePolicies<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            policies: self.policies.clone(),
+            merge: self.merge,
+        }
+    }
+}
+impl<K, V> Default for MergePolicies<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+fn merge_values<V: Merge>(values: &[&V], policy: MergePolicy) -> Option<V> {
+    let (first, higher) = values.split_first()?;
+    let mut merged = (*first).clone();
+    for value in higher {
+        merged.merge(value, policy);
+    }
+    Some(merged)
+}
+
+impl<N, K: Clone + Eq + Hash, V> Account<N, K, V> {
+    /// Returns the [`MergePolicy`] used to combine the values of a setting in the child `Accounts`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::{Account,MergePolicy};
+    ///
+    /// let mut account = Account::<(),&str,Vec<i32>>::default();
+    /// account.set_merge_policy(&"plugins", MergePolicy::Append);
+    /// assert_eq!(account.merge_policy(&"plugins"), MergePolicy::Append);
+    /// assert_eq!(account.merge_policy(&"lines"), MergePolicy::Override);
+    /// ```
+    #[must_use]
+    pub fn merge_policy(&self, setting_name: &K) -> MergePolicy {
+        self.merge.get(setting_name).unwrap_or_default()
+    }
+    /// Returns the values of a setting in the active child `Accounts` merged with `policy`,
+    /// from the lowest to the highest.
+    ///
+    /// Values in child `Accounts` below one with a tombstone for the setting are left out.
+    pub(super) fn merged(&self, setting: &K, policy: MergePolicy) -> Option<V> {
+        let merge = self.merge.merge?;
+        let mut values = vec![];
+        for account in self.layers(setting).rev() {
+            if let Some(value) = account.settings.get(setting) {
+                values.push(value);
+            }
+            if account.masks(setting) {
+                break;
+            }
+        }
+        values.reverse();
+        merge(&values, policy)
+    }
+}
+impl<N, K: Clone + Eq + Hash, V: Clone> Account<N, K, V> {
+    /// Returns the value a setting is supposed to have, according to the active child `Accounts`.
+    pub(super) fn resolve(&self, setting: &K) -> Option<V> {
+        self.merge.get(setting).map_or_else(
+            || self.get_in_sub_accounts(setting).cloned(),
+            |policy| self.merged(setting, policy),
+        )
+    }
+    /// Adds the policies of a parent `Account` to the `Account` and its child `Accounts`,
+    /// so that merged settings are combined the same way at every level.
+    pub(super) fn inherit_merge_policies(&mut self, merge: &MergePolicies<K, V>) {
+        if merge.is_empty() {
+            return;
+        }
+        for account in &mut self.accounts {
+            account.inherit_merge_policies(merge);
+        }
+        for (setting, policy) in &merge.policies {
+            self.merge.set(setting.clone(), *policy);
+        }
+        self.merge.merge = merge.merge;
+        self.snapshot.clear();
+        for (setting, policy) in &merge.policies {
+            if let Some(value) = self.merged(setting, *policy) {
+                self.subscriptions
+                    .insert(&mut self.settings, setting.clone(), value);
+            }
+        }
+    }
+}
+impl<N, K: Clone + Eq + Hash, V: Merge> Account<N, K, V> {
+    /// Changes how the values of a setting in the child `Accounts` are combined
+    ///
+    /// The policy is also set in all the child `Accounts`, and in the ones added later with
+    /// [`push`](Account::push), so the setting is combined the same way at every level. The settings
+    /// are updated such that they contain the merged values.
+    ///
+    /// Merge policies are cloned with the `Account` but aren't serialized, and are ignored when comparing `Accounts`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::{account::{Account,MergePolicy},stg::{Setting,Stg,StgTrait}};
+    ///
+    /// let mut account = Account::<String,&str,Stg>::new(
+    ///     "Main".to_string(),
+    ///     true,
+    ///     Default::default(),
+    ///     vec![
+    ///         Account::new("Default".to_string(), true, [("plugins", vec!["git".to_string().stg()].stg())].into(), vec![]),
+    ///         Account::new("Local".to_string(), true, [("plugins", vec!["lint".to_string().stg()].stg())].into(), vec![]),
+    ///     ],
+    /// );
+    /// assert_eq!(account.get(&"plugins").unstg::<Vec<Stg>>()?, vec!["lint".to_string().stg()]);
+    ///
+    /// account.set_merge_policy(&"plugins", MergePolicy::Append);
+    /// assert_eq!(
+    ///     account.get(&"plugins").unstg::<Vec<Stg>>()?,
+    ///     vec!["git".to_string().stg(), "lint".to_string().stg()]
+    /// );
+    /// # Ok::<(), hashmap_settings::stg::StgError>(())
+    /// ```
+    pub fn set_merge_policy(&mut self, setting_name: &K, policy: MergePolicy) {
+        for account in &mut self.accounts {
+            account.set_merge_policy(setting_name, policy);
+        }
+        self.merge.set(setting_name.clone(), policy);
+        self.merge.merge = Some(merge_values::<V>);
+        self.snapshot.clear();
+        if self.sub_account_containing(setting_name).is_some() {
+            self.update_setting(setting_name);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{
+        account::{Account, Merge, MergePolicy, Valid},
+        stg::{Setting, Stg, StgTrait},
+    };
+
+    fn list(values: &[&str]) -> Stg {
+        values
+            .iter()
+            .map(|value| value.to_string().stg())
+            .collect::<Vec<_>>()
+            .stg()
+    }
+    fn account() -> Account<String, &'static str, Stg> {
+        Account::new(
+            "Main".to_string(),
+            true,
+            HashMap::new(),
+            vec![
+                Account::new(
+                    "Default".to_string(),
+                    true,
+                    [("paths", list(&["/usr/bin", "/bin"]))].into(),
+                    vec![],
+                ),
+                Account::new(
+                    "Project".to_string(),
+                    true,
+                    HashMap::new(),
+                    vec![
+                        Account::new(
+                            "Shared".to_string(),
+                            true,
+                            [("paths", list(&["/opt/bin"]))].into(),
+                            vec![],
+                        ),
+                        Account::new(
+                            "Local".to_string(),
+                            true,
+                            [("paths", list(&["~/bin", "/bin"]))].into(),
+                            vec![],
+                        ),
+                    ],
+                ),
+            ],
+        )
+    }
+
+    #[test]
+    fn policies() {
+        let mut account = account();
+        assert_eq!(account.get(&"paths"), Some(&list(&["~/bin", "/bin"])));
+
+        account.set_merge_policy(&"paths", MergePolicy::Append);
+        assert_eq!(
+            account.get(&"paths"),
+            Some(&list(&["/usr/bin", "/bin", "/opt/bin", "~/bin", "/bin"]))
+        );
+        account.set_merge_policy(&"paths", MergePolicy::Prepend);
+        assert_eq!(
+            account.get(&"paths"),
+            Some(&list(&["~/bin", "/bin", "/opt/bin", "/usr/bin", "/bin"]))
+        );
+        account.set_merge_policy(&"paths", MergePolicy::Union);
+        assert_eq!(
+            account.get(&"paths"),
+            Some(&list(&["/usr/bin", "/bin", "/opt/bin", "~/bin"]))
+        );
+        account.set_merge_policy(&"paths", MergePolicy::Override);
+        assert_eq!(account.get(&"paths"), Some(&list(&["~/bin", "/bin"])));
+        assert!(account.valid().is_valid());
+    }
+    #[test]
+    fn changes_are_merged() {
+        let mut account = account();
+        account.set_merge_policy(&"paths", MergePolicy::Union);
+        let (project, local, shared) = (
+            "Project".to_string(),
+            "Local".to_string(),
+            "Shared".to_string(),
+        );
+
+        account
+            .deep_insert(&"paths", list(&["/sbin"]), &mut vec![&local, &project])
+            .unwrap();
+        assert_eq!(
+            account.get(&"paths"),
+            Some(&list(&["/usr/bin", "/bin", "/opt/bin", "/sbin"]))
+        );
+        account
+            .deep_change_activity(false, &mut vec![&shared, &project])
+            .unwrap();
+        assert_eq!(
+            account.get(&"paths"),
+            Some(&list(&["/usr/bin", "/bin", "/sbin"]))
+        );
+        account.push(
+            Account::new(
+                "Args".to_string(),
+                true,
+                [("paths", list(&["/tmp"])), ("lines", 3.stg())].into(),
+                vec![],
+            ),
+            Valid::new_true(),
+        );
+        assert_eq!(
+            account.get(&"paths"),
+            Some(&list(&["/usr/bin", "/bin", "/sbin", "/tmp"]))
+        );
+        assert_eq!(account.get(&"lines").unstg::<i32>(), Ok(3));
+        assert_eq!(
+            account.accounts()[2].merge_policy(&"paths"),
+            MergePolicy::Union
+        );
+        account.pop(Valid::new_true());
+        assert_eq!(
+            account.get(&"paths"),
+            Some(&list(&["/usr/bin", "/bin", "/sbin"]))
+        );
+
+        let mut updated = account.clone();
+        updated.update_valid(Valid::new_false());
+        assert!(updated.valid().is_valid());
+        let snapshot = account.snapshot();
+        assert_eq!(snapshot.get(&"paths"), account.get(&"paths"));
+        assert_eq!(snapshot.to_account(), account);
+    }
+    #[test]
+    fn deep_merge() {
+        let map = |entries: &[(&str, Stg)]| {
+            entries
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.clone()))
+                .collect::<HashMap<_, _>>()
+                .stg()
+        };
+        let mut colors = map(&[
+            ("background", "black".to_string().stg()),
+            ("syntax", map(&[("keyword", "blue".to_string().stg())])),
+        ]);
+        colors.merge(
+            &map(&[
+                ("foreground", "white".to_string().stg()),
+                ("syntax", map(&[("string", "green".to_string().stg())])),
+            ]),
+            MergePolicy::DeepMerge,
+        );
+        assert_eq!(
+            colors,
+            map(&[
+                ("background", "black".to_string().stg()),
+                ("foreground", "white".to_string().stg()),
+                (
+                    "syntax",
+                    map(&[
+                        ("keyword", "blue".to_string().stg()),
+                        ("string", "green".to_string().stg()),
+                    ])
+                ),
+            ])
+        );
+
+        let mut value = 3.stg();
+        value.merge(&list(&["a"]), MergePolicy::Append);
+        assert_eq!(value, list(&["a"]));
+    }
+}
@@ -1,5 +1,22 @@
+mod account_builder;
+mod condition;
+mod diff;
+mod history;
 ///module including `Incrementable` implementations
 pub mod incrementable_implementations;
+mod lock;
+mod merge;
+mod patch;
+mod path;
+mod reorder;
+#[cfg(feature = "shared")]
+mod shared;
+mod snapshot;
+mod subscriptions;
+mod transaction;
+mod unset;
+#[cfg(feature = "async")]
+mod watch;
 
 use core::{fmt::Debug, mem::replace};
 use std::{
@@ -11,6 +28,36 @@ use std::{
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+#[doc(inline)]
+pub use self::account_builder::{AccountBuilder, BuildError};
+#[doc(inline)]
+pub use self::condition::{Condition, ConditionError};
+#[doc(inline)]
+pub use self::diff::{Change, Diff};
+#[doc(inline)]
+pub use self::history::History;
+use self::lock::LockCache;
+use self::merge::MergePolicies;
+#[doc(inline)]
+pub use self::merge::{Merge, MergePolicy};
+#[doc(inline)]
+pub use self::patch::{Conflict, Patch};
+#[doc(inline)]
+pub use self::path::{AccountPath, AccountPathError, DeepNames};
+#[cfg(feature = "shared")]
+#[doc(inline)]
+pub use self::shared::SharedAccount;
+#[doc(inline)]
+pub use self::snapshot::Snapshot;
+use self::snapshot::SnapshotCache;
+#[doc(inline)]
+pub use self::subscriptions::SubscriptionId;
+use self::subscriptions::Subscriptions;
+#[doc(inline)]
+pub use self::transaction::Transaction;
+#[cfg(feature = "async")]
+#[doc(inline)]
+pub use self::watch::Watch;
 use crate::stg::Setting;
 
 /// A [`HashMap`] wrapper for layered settings.
@@ -47,7 +94,9 @@ use crate::stg::Setting;
 ///
 ///  - [`clone`][Clone::clone]: Clone an existing Account.
 ///
-/// An `AccountBuilder` is planned to be created in the [future](https://github.com/OxidizedLoop/HashMapSettings/issues/20).
+///  - [`AccountBuilder`]: Build an Account, and its child `Accounts`, by chaining methods.
+///
+///  - [`History`]: Wrap an Account to undo and redo its changes.
 ///
 ///
 /// # [Name](Account#name)
@@ -77,6 +126,12 @@ use crate::stg::Setting;
 ///
 ///  - [`deep_change_activity`](Account::deep_change_activity): Change the activity of one of the child `Accounts`
 ///
+///  - [`set_condition`](Account::set_condition): Sets a [`Condition`] that changes the activity according to the settings of the parent `Account`
+///
+///  - [`deep_set_condition`](Account::deep_set_condition): Sets the [`Condition`] of one of the child `Accounts`
+///
+///  - [`update_conditions`](Account::update_conditions): Evaluates the conditions of all the child `Accounts`
+///
 ///
 /// # [Settings](Account#settings)
 ///
@@ -88,6 +143,10 @@ use crate::stg::Setting;
 ///
 ///  - [`get`](Account::get): Returns a reference to the value corresponding to the key
 ///
+///  - [`get_with_origin`](Account::get_with_origin): Returns the value corresponding to the key and the child `Account` it came from.
+///
+///  - [`origins`](Account::origins): Returns all the child `Accounts` that define a setting.
+///
 ///  - [`insert`](Account::insert): Inserts a key-value pair into the map.
 ///
 ///  - [`deep_insert`](Account::deep_insert): Inserts a key-value pair into the map of a child Account.
@@ -96,6 +155,28 @@ use crate::stg::Setting;
 ///
 ///  - [`deep_remove`](Account::deep_remove): Removes a key-value pair from the map of a child Account.
 ///
+///  - [`replace_settings`](Account::replace_settings): Replaces all the settings of the map.
+///
+///  - [`deep_replace_settings`](Account::deep_replace_settings): Replaces all the settings of the map of a child Account.
+///
+///  - [`unset`](Account::unset): Removes a setting and leaves a tombstone that masks the child `Accounts` below.
+///
+///  - [`deep_unset`](Account::deep_unset): Removes a setting and leaves a tombstone in a child Account.
+///
+///  - [`is_unset`](Account::is_unset): Returns `true` if the setting was unset by a tombstone.
+///
+///  - [`unset_by`](Account::unset_by): Returns the child `Account` holding the tombstone of a setting.
+///
+///  - [`lock`](Account::lock): Locks a setting, so the `Accounts` above this one can't override it.
+///
+///  - [`deep_lock`](Account::deep_lock): Locks a setting in a child Account.
+///
+///  - [`unlock`](Account::unlock): Unlocks a setting.
+///
+///  - [`deep_unlock`](Account::deep_unlock): Unlocks a setting in a child Account.
+///
+///  - [`is_locked`](Account::is_locked): Returns `true` if the setting is locked.
+///
 ///  - [`keys`](Account::keys): An iterator visiting all keys in arbitrary order
 ///
 ///  - [`contains_key`](Account::contains_key): Returns `true` if the `Account` contains a value for the specified key.
@@ -110,6 +191,24 @@ use crate::stg::Setting;
 ///
 ///  - [`update_setting`](Account::update_all_settings): Updates all settings currently present in the Account with the value they are supposed to have.
 ///
+///  - [`set_merge_policy`](Account::set_merge_policy): Changes how the values of a setting in the child `Accounts` are combined.
+///
+///  - [`merge_policy`](Account::merge_policy): Returns the [`MergePolicy`] of a setting.
+///
+///  - [`diff`](Account::diff): Returns the differences between two `Accounts`.
+///
+///  - [`apply_patch`](Account::apply_patch): Applies the changes of a [`Patch`], returning the ones that conflicted.
+///
+///  - [`snapshot`](Account::snapshot): Returns an immutable [`Snapshot`] of the `Account`, that can be used to [`restore`](Account::restore) it.
+///
+///  - [`subscribe`](Account::subscribe): Registers a callback that is called when the value of a setting changes.
+///
+///  - [`subscribe_all`](Account::subscribe_all): Registers a callback that is called when the value of any setting changes.
+///
+///  - [`unsubscribe`](Account::unsubscribe): Removes a subscription.
+///
+///  - `watch`: Returns a `Watch` that receives the value of a setting whenever it changes. Requires the "async" feature.
+///
 ///
 /// # [Accounts](Account#accounts)
 ///
@@ -139,6 +238,22 @@ use crate::stg::Setting;
 ///
 ///  - [`deep_pop`](Account::deep_pop): Removes the last element from a vector of a child `Account`
 ///
+///  - [`insert_account`](Account::insert_account): Inserts an `Account` at a position within the `Vec`.
+///
+///  - [`deep_insert_account`](Account::deep_insert_account): Inserts an `Account` at a position within the `Vec` of a child `Account`.
+///
+///  - [`remove_account`](Account::remove_account): Removes the `Account` with a name from the `Vec` and returns it.
+///
+///  - [`deep_remove_account`](Account::deep_remove_account): Removes the `Account` with a name from the `Vec` of a child `Account`.
+///
+///  - [`move_account`](Account::move_account): Moves an `Account` to another position within the `Vec`.
+///
+///  - [`deep_move_account`](Account::deep_move_account): Moves an `Account` to another position within the `Vec` of a child `Account`.
+///
+///  - [`swap_accounts`](Account::swap_accounts): Swaps the positions of two `Accounts` in the `Vec`.
+///
+///  - [`deep_swap_accounts`](Account::deep_swap_accounts): Swaps the positions of two `Accounts` in the `Vec` of a child `Account`.
+///
 ///
 /// # [Valid](Account#valid)
 ///  
@@ -195,11 +310,20 @@ use crate::stg::Setting;
 /// is the a direct child of the `Account` we call the function on, and the left most is the the `Account`
 /// we will interact with.
 ///
+/// Instead of the `Vec` they also accept an [`AccountPath`], that lists the names in the natural order,
+/// from the direct child to the `Account` we will interact with, and can be parsed from a `str` like `"Default/Editor/Colors"`.
+/// Both are accepted through the [`DeepNames`] trait.
+///
+/// A `Vec` is always left empty by the deep function, even when it returns an error,
+/// so a new one is needed for each call, while an `AccountPath` is only borrowed and can be reused.
+///
 /// Deep functions can return [`DeepError`]'s
 ///
 /// The main function is [deep](Account::deep) to get a reference to a child `Account`,
 /// [deep_mut](Account::deep_mut) exists but it can make an Account [invalid](Account#valid)
 /// so its recommend to use the `deep` version of methods instead
+///
+/// Multiple deep functions can be applied atomically with [transaction](Account::transaction).
 ///  
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
@@ -208,8 +332,25 @@ pub struct Account<N, K, V> {
     active: bool,
     #[cfg_attr(feature = "serde", serde(bound = "K: Eq + Hash"))]
     settings: HashMap<K, V>,
-    accounts: Vec<Account<N, K, V>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    unset: Vec<K>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    locked: Vec<K>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    locks: LockCache<K>,
+    accounts: Vec<Self>,
     valid: Valid,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    subscriptions: Subscriptions<K, V>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    snapshot: SnapshotCache<N, K, V>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    merge: MergePolicies<K, V>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    condition: Option<Condition<K, V>>,
+    /// Settings changed by the conditions of the child `Accounts`, that the parent `Account` has to update.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pending: Vec<K>,
 }
 
 impl<N, K, V> Account<N, K, V> {
@@ -273,8 +414,16 @@ impl<N, K, V> Account<N, K, V> {
             name,
             active,
             settings,
+            unset: Vec::new(),
+            locked: Vec::new(),
             accounts,
             valid,
+            subscriptions: Subscriptions::new(),
+            locks: LockCache::new(),
+            snapshot: SnapshotCache::new(),
+            merge: MergePolicies::new(),
+            condition: None,
+            pending: Vec::new(),
         }
     }
     /// Returns the name of the `Account`
@@ -529,6 +678,8 @@ impl<N, K, V> Account<N, K, V> {
     /// ```
     #[must_use]
     pub fn get_mut_account(&mut self, index: usize) -> Option<&mut Self> {
+        // the child `Account` can be changed in any way
+        self.locks.clear();
         self.accounts.get_mut(index)
     }
     fn update_valid_children(&self) -> bool {
@@ -653,7 +804,11 @@ impl<N: PartialEq, K, V> Account<N, K, V> {
     /// assert_eq!(account.deep(&mut vec![&"3_2".to_string(),&"3".to_string()])?.get(&"answer".to_string()), Some(&42));
     /// # Ok::<(), hashmap_settings::account::DeepError>(())
     /// ```
-    pub fn deep(&self, account_names: &mut Vec<&N>) -> Result<&Self, DeepError> {
+    pub fn deep<'a>(&self, account_names: impl DeepNames<'a, N>) -> Result<&Self, DeepError>
+    where
+        N: 'a,
+    {
+        let account_names = &mut account_names.deep_names();
         let Some(account_to_find) = account_names.pop() else {
             return Err(DeepError::EmptyVec); //error if the original call is empty, but this will create the base case in the recursive call
         };
@@ -664,7 +819,7 @@ impl<N: PartialEq, K, V> Account<N, K, V> {
                     //recursive call
                     Err(error) => match error {
                         DeepError::EmptyVec => Ok(found_account), //base case
-                        DeepError::NotFound => Err(error),        //error/bad function call
+                        DeepError::NotFound | DeepError::Locked => Err(error), //error/bad function call
                     },
                     Ok(value) => Ok(value),
                 },
@@ -714,7 +869,14 @@ impl<N: PartialEq, K, V> Account<N, K, V> {
     /// assert_eq!(account.deep(&mut vec![&"3_2".to_string(),&"3".to_string()])?.get(&"answer".to_string()), Some(&777));
     /// # Ok::<(), hashmap_settings::account::DeepError>(())
     /// ```
-    pub fn deep_mut(&mut self, account_names: &mut Vec<&N>) -> Result<&mut Self, DeepError> {
+    pub fn deep_mut<'a>(
+        &mut self,
+        account_names: impl DeepNames<'a, N>,
+    ) -> Result<&mut Self, DeepError>
+    where
+        N: 'a,
+    {
+        let account_names = &mut account_names.deep_names();
         let Some(account_to_find) = account_names.pop() else {
             return Err(DeepError::EmptyVec); //error if the original call is empty, but this will create the base case in the recursive call
         };
@@ -732,7 +894,7 @@ impl<N: PartialEq, K, V> Account<N, K, V> {
                     DeepError::EmptyVec => {
                         unreachable!() //Ok(found_account)
                     } //base case
-                    DeepError::NotFound => Err(error), //error/bad function call
+                    DeepError::NotFound | DeepError::Locked => Err(error), //error/bad function call
                 },
             }
         } else {
@@ -755,6 +917,25 @@ impl<N: PartialEq, K, V> Account<N, K, V> {
         }
         None
     }
+    /// Returns the `Account` and every child `Account` in the path, starting with the `Account` itself.
+    ///
+    /// The path is in the same order as in [deep functions](Account#deep-functions).
+    fn walk(&self, path: &[N]) -> Result<Vec<&Self>, DeepError> {
+        let mut accounts = vec![self];
+        for name in path.iter().rev() {
+            let Some(child) = accounts[accounts.len() - 1].account_from_name(name) else {
+                return Err(DeepError::NotFound);
+            };
+            accounts.push(child);
+        }
+        Ok(accounts)
+    }
+    fn walk_mut(&mut self, path: &[N]) -> Option<&mut Self> {
+        match path.split_last() {
+            Some((name, path)) => self.mut_account_from_name(name)?.walk_mut(path),
+            None => Some(self),
+        }
+    }
 }
 impl<N, K: Eq + Hash, V> Account<N, K, V> {
     /// Returns the value corresponding to the key.
@@ -786,7 +967,8 @@ impl<N, K: Eq + Hash, V> Account<N, K, V> {
     ///
     /// [module-level documentation]: std::collections#insert-and-complex-keys
     ///
-    /// This method is a direct call to [`HashMap`]'s [`insert()`](HashMap::insert()).
+    /// This method is a direct call to [`HashMap`]'s [`insert()`](HashMap::insert()),
+    /// that also removes the tombstone left by [`unset`](Account::unset).
     ///
     /// # Examples
     ///
@@ -801,11 +983,14 @@ impl<N, K: Eq + Hash, V> Account<N, K, V> {
     /// assert!(account.hashmap()[&"a small number"] == 3);
     /// ```
     pub fn insert(&mut self, setting_name: K, setting_value: V) -> Option<V> {
-        self.settings.insert(setting_name, setting_value)
+        self.remove_tombstone(&setting_name);
+        self.subscriptions
+            .insert(&mut self.settings, setting_name, setting_value)
     }
     /// Removes a setting from the map, returning the value at the key if the key was previously in the map.
     ///
-    /// This method is a direct call to [`HashMap`]'s [`remove()`](HashMap::remove).
+    /// This method is a direct call to [`HashMap`]'s [`remove()`](HashMap::remove),
+    /// that also removes the tombstone left by [`unset`](Account::unset).
     ///
     /// # Examples
     ///
@@ -817,7 +1002,9 @@ impl<N, K: Eq + Hash, V> Account<N, K, V> {
     /// assert_eq!(account.remove(&"a small number"), None);
     /// ```
     pub fn remove(&mut self, setting_to_remove: &K) -> Option<V> {
-        self.settings.remove(setting_to_remove)
+        self.remove_tombstone(setting_to_remove);
+        self.subscriptions
+            .remove(&mut self.settings, setting_to_remove)
     }
     /// Returns `true` if the `Account` contains a value for the specified key.
     ///
@@ -838,18 +1025,132 @@ impl<N, K: Eq + Hash, V> Account<N, K, V> {
     pub fn contains_key(&self, setting_name: &K) -> bool {
         self.settings.contains_key(setting_name)
     }
+}
+impl<N, K: Clone + Eq + Hash, V> Account<N, K, V> {
     fn get_in_sub_accounts(&self, setting: &K) -> Option<&V> {
-        for account in (0..self.len()).rev() {
-            if self.accounts[account].active
-                && let Some(value) = self.accounts[account].settings.get(setting)
-            {
+        for account in self.layers(setting).rev() {
+            if let Some(value) = account.settings.get(setting) {
                 return Some(value);
             }
+            if account.is_unset(setting) {
+                return None;
+            }
         }
         None
     }
+    fn sub_account_containing(&self, setting: &K) -> Option<&Self> {
+        self.layers(setting)
+            .rev()
+            .find(|account| account.settings.contains_key(setting))
+    }
+}
+impl<N: Clone, K: Clone + Eq + Hash, V> Account<N, K, V> {
+    /// Returns a reference to the value corresponding to the key, along with the names
+    /// of the child `Accounts` the value was taken from.
+    ///
+    /// The names are in order from the direct child of the `Account` this method is called on,
+    /// to the deepest active `Account` that supplied the value. An empty `Vec` means that
+    /// the value isn't present in any active child `Account`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use hashmap_settings::account::Account;
+    /// let account = Account::<String,&str,i32>::new(
+    ///     "Parent Account".to_string(),
+    ///     Default::default(),
+    ///     Default::default(),
+    ///     vec![
+    ///         Account::new("Default".to_string(), true, HashMap::from([("answer",0)]), Default::default()),
+    ///         Account::new("Local".to_string(), true, Default::default(), vec![
+    ///             Account::new("Local_1".to_string(), true, HashMap::from([("answer",42)]), Default::default()),
+    ///             Account::new("Local_2".to_string(), false, HashMap::from([("answer",1)]), Default::default()),
+    ///         ]),
+    ///     ],
+    /// );
+    ///
+    /// assert_eq!(
+    ///     account.get_with_origin(&"answer"),
+    ///     Some((&42, vec!["Local".to_string(), "Local_1".to_string()]))
+    /// );
+    /// assert_eq!(account.get_with_origin(&"question"), None);
+    /// ```
+    #[must_use]
+    pub fn get_with_origin(&self, setting_name: &K) -> Option<(&V, Vec<N>)> {
+        let value = self.get(setting_name)?;
+        let mut path = vec![];
+        let mut account = self;
+        while let Some(child) = account.sub_account_containing(setting_name) {
+            path.push(child.name.clone());
+            account = child;
+        }
+        Some((value, path))
+    }
+    /// Returns all child `Accounts` that define a setting, in order of priority.
+    ///
+    /// An `Account` defines a setting if it contains it and none of its active children do.
+    /// Child `Accounts` whose value is shadowed by a higher layer, or that are inactive, are also returned,
+    /// the first [`Origin`] that is [active](Origin::active) being the one that supplied the value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use hashmap_settings::account::Account;
+    /// let account = Account::<String,&str,i32>::new(
+    ///     "Parent Account".to_string(),
+    ///     Default::default(),
+    ///     Default::default(),
+    ///     vec![
+    ///         Account::new("Default".to_string(), true, HashMap::from([("answer",0)]), Default::default()),
+    ///         Account::new("Local".to_string(), true, Default::default(), vec![
+    ///             Account::new("Local_1".to_string(), true, HashMap::from([("answer",42)]), Default::default()),
+    ///             Account::new("Local_2".to_string(), false, HashMap::from([("answer",1)]), Default::default()),
+    ///         ]),
+    ///     ],
+    /// );
+    ///
+    /// let origins = account.origins(&"answer");
+    /// assert_eq!(origins.len(), 3);
+    /// assert_eq!(origins[0].path(), &vec!["Local".to_string(), "Local_2".to_string()]);
+    /// assert_eq!((origins[0].active(), origins[0].value()), (false, &1));
+    /// assert_eq!(origins[1].path(), &vec!["Local".to_string(), "Local_1".to_string()]);
+    /// assert_eq!((origins[1].active(), origins[1].value()), (true, &42));
+    /// assert_eq!(origins[2].path(), &vec!["Default".to_string()]);
+    /// assert_eq!((origins[2].active(), origins[2].value()), (true, &0));
+    /// ```
+    #[must_use]
+    pub fn origins(&self, setting_name: &K) -> Vec<Origin<'_, N, V>> {
+        let mut origins = vec![];
+        self.origins_helper(setting_name, &mut vec![], true, &mut origins);
+        origins
+    }
+    fn origins_helper<'a>(
+        &'a self,
+        setting_name: &K,
+        path: &mut Vec<N>,
+        active: bool,
+        origins: &mut Vec<Origin<'a, N, V>>,
+    ) {
+        for account in self.accounts.iter().rev() {
+            path.push(account.name.clone());
+            let active = active && account.active;
+            if let Some(value) = account.settings.get(setting_name)
+                && account.sub_account_containing(setting_name).is_none()
+            {
+                origins.push(Origin {
+                    path: path.clone(),
+                    active,
+                    value,
+                });
+            }
+            account.origins_helper(setting_name, path, active, origins);
+            path.pop();
+        }
+    }
 }
-impl<N, K: Eq + Hash, V: PartialEq> Account<N, K, V> {
+impl<N, K: Clone + Eq + Hash, V: PartialEq> Account<N, K, V> {
     fn update_valid_settings(&self) -> bool {
         let mut hash_set = HashSet::new();
         for account in self.accounts() {
@@ -857,13 +1158,17 @@ impl<N, K: Eq + Hash, V: PartialEq> Account<N, K, V> {
                 return false;
             }
             if account.active {
-                for setting in account.keys() {
+                for setting in account.layer_keys() {
                     hash_set.insert(setting);
                 }
             }
         }
         for setting in hash_set {
-            if self.get_in_sub_accounts(setting) != self.get(setting) {
+            if let Some(policy) = self.merge.get(setting) {
+                if self.merged(setting, policy).as_ref() != self.get(setting) {
+                    return false;
+                }
+            } else if self.get_in_sub_accounts(setting) != self.get(setting) {
                 return false;
             }
         }
@@ -887,15 +1192,25 @@ impl<N, K: Clone + Eq + Hash, V: Clone> Account<N, K, V> {
     ///  //TODO(Example)
     /// ```
     pub fn update_setting(&mut self, setting: &K) {
-        for account in (0..self.len()).rev() {
-            if self.accounts[account].active
-                && let Some(value) = self.accounts[account].settings.get(setting)
-            {
-                self.settings.insert(setting.to_owned(), value.clone());
-                return;
-            }
+        self.refresh_setting(setting);
+        self.update_conditioned(setting);
+    }
+    /// Updates the settings changed by the conditions of the child `Accounts`, after `setting` was updated.
+    fn update_conditioned(&mut self, setting: &K) {
+        self.update_pending();
+        if self.conditions_read(setting) {
+            // cycles are returned by `update_conditions`
+            let _ = self.evaluate_conditions();
+        }
+    }
+    fn refresh_setting(&mut self, setting: &K) {
+        self.refresh_lock(setting);
+        if let Some(value) = self.resolve(setting) {
+            self.subscriptions
+                .insert(&mut self.settings, setting.to_owned(), value);
+        } else {
+            self.subscriptions.remove(&mut self.settings, setting);
         }
-        self.settings.remove(setting);
     }
     /// Updates a group of settings with the value they are supposed to have.
     ///
@@ -910,16 +1225,8 @@ impl<N, K: Clone + Eq + Hash, V: Clone> Account<N, K, V> {
     ///  //TODO(Example)
     /// ```
     pub fn update_vec(&mut self, settings: &Vec<&K>) {
-        'setting: for setting in settings {
-            for account in (0..self.len()).rev() {
-                if self.accounts[account].active
-                    && let Some(value) = self.accounts[account].settings.get(*setting)
-                {
-                    self.settings.insert((*setting).to_owned(), value.clone());
-                    continue 'setting;
-                }
-            }
-            self.settings.remove(*setting);
+        for setting in settings {
+            self.update_setting(setting);
         }
     }
     /// Updates all settings in the Account with the value they are supposed to have.
@@ -940,17 +1247,53 @@ impl<N, K: Clone + Eq + Hash, V: Clone> Account<N, K, V> {
             .keys()
             .map(std::borrow::ToOwned::to_owned)
             .collect::<Vec<_>>();
-        'setting: for setting in settings {
-            for account in (0..self.len()).rev() {
-                if self.accounts[account].active
-                    && let Some(value) = self.accounts[account].settings.get(&setting.clone())
-                {
-                    self.settings.insert(setting.clone(), value.clone());
-                    continue 'setting;
-                }
+        for setting in settings {
+            self.update_setting(&setting);
+        }
+    }
+    /// Replaces all the settings of the `Account`, returning the old values of the settings that were
+    /// replaced or removed.
+    ///
+    /// Settings that aren't in the new map are removed, so this is meant for `Accounts` without child `Accounts`,
+    /// like the layers created from a file. Use [`deep_replace_settings`](Account::deep_replace_settings)
+    /// to replace the settings of a child `Account` keeping the parent `Account` [valid](Account#valid).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use hashmap_settings::account::Account;
+    /// let mut account: Account<(),&str,i32> = Default::default();
+    /// account.insert("lines", 3);
+    /// account.insert("words", 2);
+    ///
+    /// let old = account.replace_settings([("lines", 5), ("width", 80)].into());
+    /// assert_eq!(old, HashMap::from([("lines", 3), ("words", 2)]));
+    /// assert_eq!(account.hashmap(), &HashMap::from([("lines", 5), ("width", 80)]));
+    /// ```
+    pub fn replace_settings(&mut self, settings: HashMap<K, V>) -> HashMap<K, V> {
+        let removed = self
+            .settings
+            .keys()
+            .filter(|setting| !settings.contains_key(setting))
+            .cloned()
+            .collect::<Vec<_>>();
+        let mut old = HashMap::new();
+        for setting in removed {
+            if let Some(value) = self.subscriptions.remove(&mut self.settings, &setting) {
+                old.insert(setting, value);
             }
-            self.settings.remove(&setting);
         }
+        for (setting, value) in settings {
+            self.remove_tombstone(&setting);
+            if let Some(value) =
+                self.subscriptions
+                    .insert(&mut self.settings, setting.clone(), value)
+            {
+                old.insert(setting, value);
+            }
+        }
+        old
     }
     fn fix_valid_settings(&mut self) {
         for account in &mut self.accounts {
@@ -963,26 +1306,19 @@ impl<N, K: Clone + Eq + Hash, V: Clone> Account<N, K, V> {
         for account in self.accounts() {
             //get set off all settings
             if account.active {
-                for setting in account.keys() {
+                for setting in account.layer_keys() {
                     all_settings.insert(setting.clone());
                 }
             }
         }
-        'setting: for setting in all_settings {
+        for setting in all_settings {
             //update settings on self account
-            for account in (0..self.len()).rev() {
-                if self.accounts[account].active
-                    && let Some(value) = self.accounts[account].settings.get(&setting)
-                {
-                    self.settings.insert(setting, value.clone());
-                    continue 'setting;
-                }
-            }
+            self.update_setting(&setting);
         }
         self.valid.settings = true;
     }
 }
-impl<N: Eq + Hash, K: Eq + Hash, V: PartialEq> Account<N, K, V> {
+impl<N: Eq + Hash, K: Clone + Eq + Hash, V: PartialEq> Account<N, K, V> {
     /// Updates `valid` to the values it's supposed to have.
     ///
     /// This method takes a [Valid], updating the `Account`'s [Valid] accordingly.
@@ -1079,11 +1415,15 @@ impl<N: PartialEq, K: Clone + Eq + Hash, V: Clone> Account<N, K, V> {
     ///     ],
     /// ));
     /// ```
-    pub fn deep_change_activity(
+    pub fn deep_change_activity<'a>(
         &mut self,
         new_active: bool,
-        account_names: &mut Vec<&N>,
-    ) -> Result<bool, DeepError> {
+        account_names: impl DeepNames<'a, N>,
+    ) -> Result<bool, DeepError>
+    where
+        N: 'a,
+    {
+        let account_names = &mut account_names.deep_names();
         self.deep_change_activity_helper(new_active, account_names)
             .0
     }
@@ -1099,6 +1439,9 @@ impl<N: PartialEq, K: Clone + Eq + Hash, V: Clone> Account<N, K, V> {
     ///
     /// Deep functions can return [`DeepError`]'s
     ///
+    /// Returns [`DeepError::Locked`] if the setting is [locked](Account::lock) below the child `Account`,
+    /// as the value wouldn't be used.
+    ///
     /// # Examples
     ///
     /// ```
@@ -1131,20 +1474,29 @@ impl<N: PartialEq, K: Clone + Eq + Hash, V: Clone> Account<N, K, V> {
     /// assert_eq!(account.deep(&mut vec![&"3_2".to_string(),&"3".to_string()])?.get(&"answer".to_string()), Some(&777));
     /// # Ok::<(), hashmap_settings::account::DeepError>(())
     /// ```
-    pub fn deep_insert(
+    pub fn deep_insert<'a>(
         &mut self,
         setting_name: &K,
         setting_value: V,
-        account_names: &mut Vec<&N>,
-    ) -> Result<Option<V>, DeepError> {
+        account_names: impl DeepNames<'a, N>,
+    ) -> Result<Option<V>, DeepError>
+    where
+        N: 'a,
+    {
+        let account_names = &mut account_names.deep_names();
         let Some(account_to_find) = account_names.pop() else {
             return Err(DeepError::EmptyVec); //error if the original call is empty, but this will create the base case in the recursive call
         };
+        if self.locked_below(account_to_find, setting_name) {
+            return Err(DeepError::Locked);
+        }
         #[allow(clippy::option_if_let_else)]
         if let Some(found_account) = self.mut_account_from_name(account_to_find) {
             if account_names.is_empty() {
                 //this and the unreachable()! have been added to prevent a .clone() on setting_value
-                return Ok(found_account.insert(setting_name.to_owned(), setting_value));
+                let insert_option = found_account.insert(setting_name.to_owned(), setting_value);
+                self.update_setting(setting_name);
+                return Ok(insert_option);
             }
             match found_account.deep_insert(setting_name, setting_value, account_names) {
                 //recursive call
@@ -1158,7 +1510,7 @@ impl<N: PartialEq, K: Clone + Eq + Hash, V: Clone> Account<N, K, V> {
                     DeepError::EmptyVec => {
                         unreachable!()
                     } //base case
-                    DeepError::NotFound => Err(error), //error/bad function call
+                    DeepError::NotFound | DeepError::Locked => Err(error), //error/bad function call
                 },
             }
         } else {
@@ -1208,11 +1560,15 @@ impl<N: PartialEq, K: Clone + Eq + Hash, V: Clone> Account<N, K, V> {
     /// assert_eq!(account.deep(&mut vec![&"3_2".to_string(),&"3".to_string()])?.get(&"int".to_string()), None);
     /// # Ok::<(), hashmap_settings::account::DeepError>(())
     /// ```
-    pub fn deep_remove(
+    pub fn deep_remove<'a>(
         &mut self,
         setting_to_remove: &K,
-        account_names: &mut Vec<&N>,
-    ) -> Result<Option<V>, DeepError> {
+        account_names: impl DeepNames<'a, N>,
+    ) -> Result<Option<V>, DeepError>
+    where
+        N: 'a,
+    {
+        let account_names = &mut account_names.deep_names();
         let Some(account_to_find) = account_names.pop() else {
             return Err(DeepError::EmptyVec); //error if the original call is empty, but this will create the base case in the recursive call
         };
@@ -1227,14 +1583,88 @@ impl<N: PartialEq, K: Clone + Eq + Hash, V: Clone> Account<N, K, V> {
                     Ok(insert_option) //returning the original value from the base case
                 }
                 Err(error) => match error {
-                    DeepError::EmptyVec => Ok(found_account.remove(setting_to_remove)), //base case
-                    DeepError::NotFound => Err(error), //error/bad function call
+                    DeepError::EmptyVec => {
+                        let remove_option = found_account.remove(setting_to_remove);
+                        self.update_setting(setting_to_remove);
+                        Ok(remove_option)
+                    } //base case
+                    DeepError::NotFound | DeepError::Locked => Err(error), //error/bad function call
                 },
             }
         } else {
             Err(DeepError::NotFound)
         }
     }
+    /// Replaces all the settings of a child `Account`, returning the old values of the settings that were
+    /// replaced or removed.
+    ///
+    /// Part of the [deep functions](Account#deep-functions) group that accept a `Vec` of &N to identify
+    /// the child `Account` to run the function. [`replace_settings`](Account::replace_settings) in this case.
+    ///
+    /// Both the replaced and the removed settings are updated in all the affected accounts such that they
+    /// contain the correct values.
+    ///
+    /// # Errors
+    ///
+    /// Deep functions can return [`DeepError`]'s
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use hashmap_settings::account::Account;
+    /// let mut account = Account::<String,&str,i32>::new(
+    ///     "Main".to_string(),
+    ///     true,
+    ///     Default::default(),
+    ///     vec![
+    ///         Account::new("Default".to_string(), true, [("lines", 3), ("words", 2)].into(), vec![]),
+    ///         Account::new("Local".to_string(), true, [("lines", 5), ("width", 80)].into(), vec![]),
+    ///     ],
+    /// );
+    ///
+    /// let old = account.deep_replace_settings([("words", 7)].into(), &mut vec![&"Local".to_string()])?;
+    /// assert_eq!(old, HashMap::from([("lines", 5), ("width", 80)]));
+    /// assert_eq!(account.hashmap(), &HashMap::from([("lines", 3), ("words", 7)]));
+    /// # Ok::<(), hashmap_settings::account::DeepError>(())
+    /// ```
+    pub fn deep_replace_settings<'a>(
+        &mut self,
+        settings: HashMap<K, V>,
+        account_names: impl DeepNames<'a, N>,
+    ) -> Result<HashMap<K, V>, DeepError>
+    where
+        N: 'a,
+    {
+        let account_names = &mut account_names.deep_names();
+        self.deep_replace_settings_helper(settings, account_names)
+            .map(|(old, _)| old)
+    }
+    fn deep_replace_settings_helper(
+        &mut self,
+        settings: HashMap<K, V>,
+        account_names: &mut Vec<&N>,
+    ) -> Result<(HashMap<K, V>, Vec<K>), DeepError> {
+        let Some(account_to_find) = account_names.pop() else {
+            return Err(DeepError::EmptyVec);
+        };
+        let Some(found_account) = self.mut_account_from_name(account_to_find) else {
+            return Err(DeepError::NotFound);
+        };
+        let (old, changed) = if account_names.is_empty() {
+            let changed = found_account
+                .keys()
+                .filter(|setting| !settings.contains_key(setting))
+                .chain(settings.keys())
+                .cloned()
+                .collect::<Vec<_>>();
+            (found_account.replace_settings(settings), changed)
+        } else {
+            found_account.deep_replace_settings_helper(settings, account_names)?
+        };
+        self.update_vec(&changed.iter().collect());
+        Ok((old, changed))
+    }
     fn deep_change_activity_helper(
         &mut self,
         new_active: bool,
@@ -1254,14 +1684,17 @@ impl<N: PartialEq, K: Clone + Eq + Hash, V: Clone> Account<N, K, V> {
                     (Ok(insert_option), settings) //returning the original value from the base case
                 }
                 (Err(error), _) => match error {
-                    DeepError::EmptyVec => (
-                        Ok(found_account.change_activity(new_active)),
-                        found_account
-                            .keys()
+                    DeepError::EmptyVec => {
+                        let changed = found_account.change_activity(new_active);
+                        let settings = found_account
+                            .layer_keys()
+                            .into_iter()
                             .map(std::borrow::ToOwned::to_owned)
-                            .collect::<Vec<_>>(),
-                    ), //base case
-                    DeepError::NotFound => (Err(error), vec![]), //error/bad function call
+                            .collect::<Vec<_>>();
+                        self.update_vec(&settings.iter().collect());
+                        (Ok(changed), settings)
+                    } //base case
+                    DeepError::NotFound | DeepError::Locked => (Err(error), vec![]), //error/bad function call
                 },
             }
         } else {
@@ -1287,19 +1720,20 @@ impl<N, K: Clone + Eq + Hash, V: Clone + PartialEq> Account<N, K, V> {
     /// ```
     #[must_use = "if return value isn't needed use update_setting() instead"]
     pub fn update_setting_returns(&mut self, setting: &K) -> Option<bool> {
-        for account in (0..self.len()).rev() {
-            if self.accounts[account].active
-                && let Some(value) = self.accounts[account].settings.get(setting)
-            {
-                return Some(
-                    !self
-                        .settings
-                        .insert(setting.to_owned(), value.clone())
-                        .is_some_and(|x| &x == value),
-                );
-            }
-        }
-        self.settings.remove(setting).map(|_| true)
+        self.refresh_lock(setting);
+        let updated = if let Some(value) = self.resolve(setting) {
+            Some(
+                self.subscriptions
+                    .insert(&mut self.settings, setting.to_owned(), value.clone())
+                    .is_none_or(|x| x != value),
+            )
+        } else {
+            self.subscriptions
+                .remove(&mut self.settings, setting)
+                .map(|_| true)
+        };
+        self.update_conditioned(setting);
+        updated
     }
 }
 impl<N: Clone + Eq + Hash + Incrementable + PartialEq, K, V> Account<N, K, V> {
@@ -1350,11 +1784,15 @@ impl<N: Clone + Eq + Hash + Incrementable + PartialEq, K, V> Account<N, K, V> {
     ///     ],
     /// ));
     /// ```
-    pub fn deep_rename(
+    pub fn deep_rename<'a>(
         &mut self,
         new_name: &N,
-        account_names: &mut Vec<&N>,
-    ) -> Result<N, DeepError> {
+        account_names: impl DeepNames<'a, N>,
+    ) -> Result<N, DeepError>
+    where
+        N: 'a,
+    {
+        let account_names = &mut account_names.deep_names();
         let Some(account_to_find) = account_names.pop() else {
             return Err(DeepError::EmptyVec); //error if the original call is empty, but this will create the base case in the recursive call
         };
@@ -1371,7 +1809,7 @@ impl<N: Clone + Eq + Hash + Incrementable + PartialEq, K, V> Account<N, K, V> {
                         self.fix_valid_names();
                         Ok(n)
                     } //base case
-                    DeepError::NotFound => Err(error), //error/bad function call
+                    DeepError::NotFound | DeepError::Locked => Err(error), //error/bad function call
                 },
             }
         } else {
@@ -1385,6 +1823,9 @@ impl<N: Eq + Hash, K: Clone + Eq + Hash, V: Clone + PartialEq> Account<N, K, V>
     /// Depending on the [Valid] provided it could make the parent `Account` [invalid](Account#valid).
     /// Providing a `Valid::new_true()` will always result in a valid `Account` so it is recommended.
     ///
+    /// When the [Valid] provided has `settings` and the removed `Account` was active, the settings it
+    /// held are updated, even if the parent `Account` was already valid, so the values it provided are dropped.
+    ///
     /// This method contains a call to [`Vec`]'s [`pop()`](Vec::pop()).
     ///
     /// # Examples
@@ -1416,15 +1857,18 @@ impl<N: Eq + Hash, K: Clone + Eq + Hash, V: Clone + PartialEq> Account<N, K, V>
     /// ```
     pub fn pop(&mut self, valid: Valid) -> Option<Self> {
         let popped_account = self.accounts.pop()?;
+        self.locks.clear();
         if !self.valid.names && valid.names {
             self.valid.names = self.update_valid_names();
         }
         if !self.valid.children && valid.children {
             self.valid.children = self.update_valid_children();
         }
-        if !self.valid.settings && valid.settings && popped_account.active {
-            self.update_vec(&popped_account.keys().collect());
-            self.valid.settings = self.update_valid_settings();
+        if valid.settings && popped_account.active {
+            self.update_vec(&popped_account.layer_keys());
+            if !self.valid.settings {
+                self.valid.settings = self.update_valid_settings();
+            }
         }
         Some(popped_account)
     }
@@ -1486,11 +1930,15 @@ impl<N: Eq + Hash, K: Clone + Eq + Hash, V: Clone + PartialEq> Account<N, K, V>
     ///
     /// # Ok::<(), hashmap_settings::account::DeepError>(())
     /// ```
-    pub fn deep_pop(
+    pub fn deep_pop<'a>(
         &mut self,
         valid: Valid,
-        account_names: &mut Vec<&N>,
-    ) -> Result<Option<Self>, DeepError> {
+        account_names: impl DeepNames<'a, N>,
+    ) -> Result<Option<Self>, DeepError>
+    where
+        N: 'a,
+    {
+        let account_names = &mut account_names.deep_names();
         let Some(account_to_find) = account_names.pop() else {
             return Err(DeepError::EmptyVec); //error if the original call is empty, but this will create the base case in the recursive call
         };
@@ -1503,7 +1951,7 @@ impl<N: Eq + Hash, K: Clone + Eq + Hash, V: Clone + PartialEq> Account<N, K, V>
                         None => None,
                         Some(account) => {
                             if account.active {
-                                self.update_vec(&account.keys().collect());
+                                self.update_vec(&account.layer_keys());
                             }
                             Some(account)
                         }
@@ -1511,8 +1959,16 @@ impl<N: Eq + Hash, K: Clone + Eq + Hash, V: Clone + PartialEq> Account<N, K, V>
                     //returning the original value from the base case
                 }
                 Err(error) => match error {
-                    DeepError::EmptyVec => Ok(found_account.pop(valid)), //base case
-                    DeepError::NotFound => Err(error),                   //error/bad function call
+                    DeepError::EmptyVec => {
+                        let popped_account = found_account.pop(valid);
+                        if let Some(account) = &popped_account
+                            && account.active
+                        {
+                            self.update_vec(&account.layer_keys());
+                        }
+                        Ok(popped_account)
+                    } //base case
+                    DeepError::NotFound | DeepError::Locked => Err(error), //error/bad function call
                 },
             }
         } else {
@@ -1560,8 +2016,16 @@ impl<N: Clone + Eq + Hash + Incrementable, K: Clone + Eq + Hash, V: Clone + Part
             name,
             active,
             settings,
+            unset: Vec::new(),
+            locked: Vec::new(),
             accounts,
             valid: Valid::new_false(),
+            subscriptions: Subscriptions::new(),
+            locks: LockCache::new(),
+            snapshot: SnapshotCache::new(),
+            merge: MergePolicies::new(),
+            condition: None,
+            pending: Vec::new(),
         };
         new_account.fix_valid(Valid::new_true());
         new_account
@@ -1650,9 +2114,20 @@ impl<N: Clone + Eq + Hash + Incrementable + PartialEq, K: Clone + Eq + Hash, V:
         if self.valid.settings && valid.settings && !account.valid.is_valid() {
             self.fix_valid(Valid::new(false, true, false));
         }
+        let mut account = account;
+        account.inherit_merge_policies(&self.merge);
+        // merged, unset and locked settings are updated once the `Account` is pushed
+        let mut updated = vec![];
         if account.active {
-            for setting in account.settings.keys() {
-                self.insert(setting.to_owned(), account.get(setting).unwrap().clone());
+            for setting in account.layer_keys() {
+                if let Some(value) = account.get(setting)
+                    && !self.merge.contains(setting)
+                    && !self.is_locked(setting)
+                {
+                    self.insert(setting.to_owned(), value.clone());
+                } else {
+                    updated.push(setting.to_owned());
+                }
             }
         }
         if self.valid.names && valid.names && self.accounts_names().contains(&&account.name) {
@@ -1661,6 +2136,16 @@ impl<N: Clone + Eq + Hash + Incrementable + PartialEq, K: Clone + Eq + Hash, V:
         } else {
             self.accounts.push(account);
         }
+        self.locks.clear();
+        self.update_vec(&updated.iter().collect());
+        if self
+            .accounts
+            .iter()
+            .any(|account| account.condition.is_some())
+        {
+            // cycles are returned by `update_conditions`
+            let _ = self.evaluate_conditions();
+        }
     }
     /// Appends an `Account` to the back of the `Vec` of child `Accounts` of a child `Account`.
     ///
@@ -1725,12 +2210,16 @@ impl<N: Clone + Eq + Hash + Incrementable + PartialEq, K: Clone + Eq + Hash, V:
     ///
     /// # Ok::<(), hashmap_settings::account::DeepError>(())
     /// ```
-    pub fn deep_push(
+    pub fn deep_push<'a>(
         &mut self,
         account: Self,
         valid: Valid,
-        account_names: &mut Vec<&N>,
-    ) -> Option<DeepError> {
+        account_names: impl DeepNames<'a, N>,
+    ) -> Option<DeepError>
+    where
+        N: 'a,
+    {
+        let account_names = &mut account_names.deep_names();
         self.deep_push_helper(account, valid, account_names).err()
     }
     fn deep_push_helper(
@@ -1748,14 +2237,17 @@ impl<N: Clone + Eq + Hash + Incrementable + PartialEq, K: Clone + Eq + Hash, V:
                 //this and the unreachable()! have been added due to https://github.com/rust-lang/rust/issues/21906
                 let is_active = account.active;
                 found_account.push(account, valid);
-                return Ok(if is_active {
+                let keys = if is_active {
                     found_account.accounts[found_account.len() - 1]
-                        .keys()
+                        .layer_keys()
+                        .into_iter()
                         .map(std::borrow::ToOwned::to_owned)
                         .collect::<Vec<_>>()
                 } else {
                     vec![]
-                });
+                };
+                self.update_vec(&keys.iter().collect());
+                return Ok(keys);
             }
             match found_account.deep_push_helper(account, valid, account_names) {
                 //recursive call
@@ -1767,7 +2259,7 @@ impl<N: Clone + Eq + Hash + Incrementable + PartialEq, K: Clone + Eq + Hash, V:
                     DeepError::EmptyVec => {
                         unreachable!() //Ok(found_account)
                     } //base case
-                    DeepError::NotFound => Err(error), //error/bad function call
+                    DeepError::NotFound | DeepError::Locked => Err(error), //error/bad function call
                 },
             }
         } else {
@@ -1782,8 +2274,16 @@ impl<N: Default, K, V> Default for Account<N, K, V> {
             name: N::default(),
             active: true,
             settings: HashMap::default(),
+            unset: Vec::new(),
+            locked: Vec::new(),
             accounts: Vec::default(),
             valid: Valid::default(),
+            subscriptions: Subscriptions::new(),
+            locks: LockCache::new(),
+            snapshot: SnapshotCache::new(),
+            merge: MergePolicies::new(),
+            condition: None,
+            pending: Vec::new(),
         }
     }
 }
@@ -1793,17 +2293,28 @@ impl<N: Clone, K: Clone, V: Clone> Clone for Account<N, K, V> {
             name: self.name.clone(),
             active: self.active,
             settings: self.settings.clone(),
+            unset: self.unset.clone(),
+            locked: self.locked.clone(),
             accounts: self.accounts.clone(),
             valid: self.valid,
+            subscriptions: Subscriptions::new(),
+            locks: LockCache::new(),
+            snapshot: SnapshotCache::new(),
+            merge: self.merge.clone(),
+            condition: self.condition.clone(),
+            pending: Vec::new(),
         }
     }
 }
+#[allow(clippy::missing_fields_in_debug)]
 impl<N: Debug, K: Debug, V: Debug> Debug for Account<N, K, V> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Account")
             .field("name", &self.name)
             .field("active", &self.active)
             .field("settings", &self.settings)
+            .field("unset", &self.unset)
+            .field("locked", &self.locked)
             .field("accounts", &self.accounts)
             .field("valid", &self.valid)
             .finish()
@@ -1814,6 +2325,8 @@ impl<N: PartialEq, K: Eq + Hash, V: PartialEq> PartialEq for Account<N, K, V> {
         self.name == other.name
             && self.active == other.active
             && self.settings == other.settings
+            && same_keys(&self.unset, &other.unset)
+            && same_keys(&self.locked, &other.locked)
             && self.accounts == other.accounts
             && self.valid == other.valid
     }
@@ -1841,6 +2354,18 @@ impl<
 {
 }
 
+/// Takes the names of a deep function, keeping them in the same order.
+fn deep_path<N: Clone>(account_names: Vec<&N>) -> Result<Vec<N>, DeepError> {
+    if account_names.is_empty() {
+        return Err(DeepError::EmptyVec);
+    }
+    Ok(account_names.into_iter().cloned().collect())
+}
+/// Returns `true` if both lists of settings contain the same settings, in any order.
+fn same_keys<K: PartialEq>(keys: &[K], other: &[K]) -> bool {
+    keys.len() == other.len() && keys.iter().all(|key| other.contains(key))
+}
+
 /// `Account`'s validity tracker
 ///
 /// [`Account`] contains a valid field of type `Valid` that tracks if an [`Account`] is [valid](Account#valid).
@@ -2034,6 +2559,35 @@ pub trait Incrementable {
     fn increment_mut(&mut self);
 }
 
+/// A child `Account` that defines a setting
+///
+/// Returned by [`Account::origins`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Origin<'a, N, V> {
+    path: Vec<N>,
+    active: bool,
+    value: &'a V,
+}
+impl<N, V> Origin<'_, N, V> {
+    /// Returns the names of the `Accounts` from the direct child of the `Account` [`origins`](Account::origins)
+    /// was called on, to the `Account` that defines the setting.
+    #[must_use]
+    pub const fn path(&self) -> &Vec<N> {
+        &self.path
+    }
+    /// Returns `true` if the `Account` that defines the setting, and all the `Accounts` in its path, are
+    /// [active](Account#active).
+    #[must_use]
+    pub const fn active(&self) -> bool {
+        self.active
+    }
+    /// Returns a reference to the value of the setting in this `Account`.
+    #[must_use]
+    pub const fn value(&self) -> &V {
+        self.value
+    }
+}
+
 /// Errors involving [Deep Functions](Account#deep-functions)
 #[derive(Debug, PartialEq, Eq)]
 pub enum DeepError {
@@ -2041,4 +2595,6 @@ pub enum DeepError {
     NotFound,
     /// Error of providing a empty `Vec` to a deep function
     EmptyVec,
+    /// Error of changing a setting in a child `Account` above one where the setting is [locked](Account::lock)
+    Locked,
 }
@@ -0,0 +1,576 @@
+use core::fmt::{self, Debug, Display};
+use std::{collections::HashMap, hash::Hash};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::account::{Account, Change, Diff, Valid};
+
+/// A set of owned [`Change`]s that can be applied to an [`Account`]
+///
+/// Usually created from a [`Diff`], it can be serialized to be applied to another tree later,
+/// with [`apply_patch`](Account::apply_patch).
+///
+/// # Examples
+///
+/// ```
+/// use hashmap_settings::account::{Account,Patch};
+///
+/// let old = Account::<String,&str,i32>::new("Default".to_string(), true, [("lines", 3)].into(), vec![]);
+/// let new = Account::new("Default".to_string(), true, [("lines", 5)].into(), vec![]);
+///
+/// let patch = Patch::from(old.diff(&new));
+/// assert_eq!(patch.to_string(), "[] ~ \"lines\": 3 -> 5\n");
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Patch<N, K, V> {
+    changes: Vec<Change<N, K, V>>,
+}
+impl<N, K, V> Patch<N, K, V> {
+    /// Creates a `Patch` with the [`Change`]s provided
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::{Change,Patch};
+    ///
+    /// let patch = Patch::<String,&str,i32>::new(vec![
+    ///     Change::Added { path: vec![], key: "lines", value: 3 },
+    /// ]);
+    /// assert_eq!(patch.changes().len(), 1);
+    /// ```
+    #[must_use]
+    pub const fn new(changes: Vec<Change<N, K, V>>) -> Self {
+        Self { changes }
+    }
+    /// Returns the [`Change`]s
+    #[must_use]
+    pub const fn changes(&self) -> &Vec<Change<N, K, V>> {
+        &self.changes
+    }
+    /// Returns `true` if the `Patch` has no [`Change`]s
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+    /// Moves the `Patch` to a child `Account`, adding `path` to the start of the path of every [`Change`]
+    ///
+    /// `path` is in the same order as the path of a `Change`, starting with the direct child `Account`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::{Change,Patch};
+    ///
+    /// let patch = Patch::<String,&str,i32>::new(vec![
+    ///     Change::Added { path: vec![], key: "lines", value: 3 },
+    /// ])
+    /// .prefixed(&["Default".to_string()]);
+    /// assert_eq!(
+    ///     patch.changes(),
+    ///     &vec![Change::Added { path: vec!["Default".to_string()], key: "lines", value: 3 }]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn prefixed(mut self, path: &[N]) -> Self
+    where
+        N: Clone,
+    {
+        for change in &mut self.changes {
+            let change_path = change.path_mut();
+            change_path.splice(0..0, path.iter().cloned());
+        }
+        self
+    }
+}
+impl<N: Clone, K: Clone, V: Clone> From<Diff<'_, N, K, V>> for Patch<N, K, V> {
+    fn from(diff: Diff<'_, N, K, V>) -> Self {
+        Self::new(diff.into_iter().map(Change::cloned).collect())
+    }
+}
+impl<N, K, V> IntoIterator for Patch<N, K, V> {
+    type Item = Change<N, K, V>;
+    type IntoIter = std::vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.changes.into_iter()
+    }
+}
+impl<N: Debug, K: Debug, V: Debug> Display for Patch<N, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{change}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A [`Change`] of a [`Patch`] that couldn't be applied
+///
+/// Returned by [`apply_patch`](Account::apply_patch) when the `Account` no longer matches
+/// what the `Change` expected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Conflict<N, K, V> {
+    change: Change<N, K, V>,
+    found: Option<V>,
+}
+impl<N, K, V> Conflict<N, K, V> {
+    /// Returns the [`Change`] that wasn't applied
+    #[must_use]
+    pub const fn change(&self) -> &Change<N, K, V> {
+        &self.change
+    }
+    /// Returns the value of the setting found instead of the expected one
+    ///
+    /// This is `None` if the setting wasn't present, or if the `Change` isn't to a setting.
+    #[must_use]
+    pub const fn found(&self) -> Option<&V> {
+        self.found.as_ref()
+    }
+}
+impl<N: Debug, K: Debug, V: Debug> Display for Conflict<N, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "conflict on {}", self.change)?;
+        if let Some(found) = &self.found {
+            write!(f, ", found {found:?}")?;
+        }
+        Ok(())
+    }
+}
+
+impl<N: Clone + PartialEq, K: Clone + Eq + Hash, V: Clone + PartialEq> Account<N, K, V> {
+    /// Applies the [`Change`]s of a [`Patch`], returning the ones that conflicted
+    ///
+    /// A `Change` conflicts if the `Account` no longer matches the expected state, for example a setting
+    /// whose value isn't the old value of the `Change`, or a child `Account` that doesn't exist. Conflicting
+    /// `Change`s are skipped and every other `Change` is still applied, so settings that were customized
+    /// in the `Account` aren't lost.
+    ///
+    /// A `Change` whose result is already present, like a setting removed that isn't present,
+    /// isn't a conflict. This includes the `Changes` to the contents of a child `Account` that the `Patch`
+    /// removes, once that `Account` isn't present.
+    ///
+    /// A child `Account` is only removed if it matches the removed `Account` of the `Patch`: its settings
+    /// and child `Accounts` are removed by the `Changes` before its removal, so if settings or child `Accounts`
+    /// were added or customized in it, the removal is a conflict and the `Account` is kept.
+    ///
+    /// This will updated the [settings](Account#settings) of all necessary Accounts
+    /// so that the parent Account remains [valid](Account#valid).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::{Account,Change,Patch};
+    ///
+    /// let old = Account::<String,&str,i32>::new("Default".to_string(), true, [("lines", 3), ("words", 2)].into(), vec![]);
+    /// let new = Account::new("Default".to_string(), true, [("lines", 5), ("words", 4)].into(), vec![]);
+    /// let patch = Patch::from(old.diff(&new)).prefixed(&["Default".to_string()]);
+    ///
+    /// let mut account = Account::new(
+    ///     "Main".to_string(),
+    ///     true,
+    ///     Default::default(),
+    ///     vec![Account::new("Default".to_string(), true, [("lines", 3), ("words", 7)].into(), vec![])],
+    /// );
+    /// let conflicts = account.apply_patch(patch);
+    ///
+    /// assert_eq!(account.get(&"lines"), Some(&5));
+    /// assert_eq!(account.get(&"words"), Some(&7));
+    /// assert_eq!(conflicts.len(), 1);
+    /// assert_eq!(
+    ///     conflicts[0].change(),
+    ///     &Change::Modified { path: vec!["Default".to_string()], key: "words", old: 2, new: 4 }
+    /// );
+    /// assert_eq!(conflicts[0].found(), Some(&7));
+    /// ```
+    #[must_use = "conflicting changes aren't applied"]
+    pub fn apply_patch(&mut self, patch: Patch<N, K, V>) -> Vec<Conflict<N, K, V>> {
+        // the paths of the `Accounts` removed by the `Patch`
+        let removed = patch
+            .changes
+            .iter()
+            .filter_map(|change| match change {
+                Change::AccountRemoved { path, name, .. } => {
+                    Some([path.as_slice(), core::slice::from_ref(name)].concat())
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let mut conflicts = vec![];
+        for change in patch {
+            if let Err(found) = self.apply_change(&change)
+                && !self.already_removed(&removed, change.path())
+            {
+                conflicts.push(Conflict { change, found });
+            }
+        }
+        conflicts
+    }
+    /// Returns `true` if `path` is in one of the `removed` child `Accounts`, and that `Account` isn't present.
+    fn already_removed(&mut self, removed: &[Vec<N>], path: &[N]) -> bool {
+        removed
+            .iter()
+            .any(|removed| path.starts_with(removed) && self.child_at(removed).is_none())
+    }
+    fn apply_change(&mut self, change: &Change<N, K, V>) -> Result<(), Option<V>> {
+        match change {
+            Change::Added { path, key, value } => self.patch_setting(path, key, None, Some(value)),
+            Change::Removed { path, key, value } => {
+                self.patch_setting(path, key, Some(value), None)
+            }
+            Change::Modified {
+                path,
+                key,
+                old,
+                new,
+            } => self.patch_setting(path, key, Some(old), Some(new)),
+            Change::AccountAdded {
+                path,
+                index,
+                name,
+                active,
+            } => self.patch_account_added(path, *index, name, *active),
+            Change::AccountRemoved { path, name, .. } => {
+                let parent = self.child_at(path).ok_or(None)?;
+                let Some(index) = parent
+                    .accounts
+                    .iter()
+                    .position(|account| &account.name == name)
+                else {
+                    return Ok(());
+                };
+                // the contents the `Patch` expects are removed by the `Changes` before this one,
+                // so anything left was added or customized in this tree
+                let account = &parent.accounts[index];
+                if !account.settings.is_empty() || !account.accounts.is_empty() {
+                    return Err(None);
+                }
+                let removed = parent.accounts.remove(index);
+                if removed.active {
+                    let keys = removed
+                        .layer_keys()
+                        .into_iter()
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    self.update_path(path, &keys);
+                }
+                Ok(())
+            }
+            Change::AccountRenamed { path, old, new } => {
+                let parent = self.child_at(path).ok_or(None)?;
+                let renamed = parent.account_from_name(new).is_some();
+                match parent.mut_account_from_name(old) {
+                    Some(account) if !renamed => {
+                        account.name = new.clone();
+                        Ok(())
+                    }
+                    None if renamed => Ok(()),
+                    _ => Err(None),
+                }
+            }
+            Change::AccountsReordered { path, names } => {
+                let parent = self.child_at(path).ok_or(None)?;
+                parent.accounts.sort_by_key(|account| {
+                    names
+                        .iter()
+                        .position(|name| name == &account.name)
+                        .unwrap_or(usize::MAX)
+                });
+                let keys = parent
+                    .accounts
+                    .iter()
+                    .filter(|account| account.active)
+                    .flat_map(Self::layer_keys)
+                    .cloned()
+                    .collect::<Vec<_>>();
+                self.update_path(path, &keys);
+                Ok(())
+            }
+            Change::ActivityChanged { path, active } => {
+                let Some((_, parent_path)) = path.split_last() else {
+                    return Err(None);
+                };
+                let account = self.child_at(path).ok_or(None)?;
+                if account.active != *active {
+                    account.active = *active;
+                    let keys = account
+                        .layer_keys()
+                        .into_iter()
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    self.update_path(parent_path, &keys);
+                }
+                Ok(())
+            }
+        }
+    }
+    fn patch_account_added(
+        &mut self,
+        path: &[N],
+        index: usize,
+        name: &N,
+        active: bool,
+    ) -> Result<(), Option<V>> {
+        let parent = self.child_at(path).ok_or(None)?;
+        if parent.account_from_name(name).is_some() {
+            return Ok(());
+        }
+        let index = index.min(parent.len());
+        parent.accounts.insert(
+            index,
+            Self::new_unchecked(
+                name.clone(),
+                active,
+                HashMap::new(),
+                vec![],
+                Valid::new_true(),
+            ),
+        );
+        parent.accounts[index].inherit_merge_policies(&parent.merge);
+        Ok(())
+    }
+    fn patch_setting(
+        &mut self,
+        path: &[N],
+        key: &K,
+        old: Option<&V>,
+        new: Option<&V>,
+    ) -> Result<(), Option<V>> {
+        let account = self.child_at(path).ok_or(None)?;
+        let current = account.settings.get(key);
+        if current == new {
+            return Ok(());
+        }
+        if current != old {
+            return Err(current.cloned());
+        }
+        match new {
+            Some(new) => {
+                account
+                    .subscriptions
+                    .insert(&mut account.settings, key.clone(), new.clone())
+            }
+            None => account.subscriptions.remove(&mut account.settings, key),
+        };
+        if let Some((_, parent_path)) = path.split_last() {
+            self.update_path(parent_path, core::slice::from_ref(key));
+        }
+        Ok(())
+    }
+    /// Returns the child `Account` at `path`, in the order of the path of a `Change`.
+    fn child_at(&mut self, path: &[N]) -> Option<&mut Self> {
+        path.iter()
+            .try_fold(self, |account, name| account.mut_account_from_name(name))
+    }
+    /// Updates `keys` in the `Account` at `path` and in every `Account` above it.
+    fn update_path(&mut self, path: &[N], keys: &[K]) {
+        let keys = keys.iter().collect();
+        for depth in (0..=path.len()).rev() {
+            if let Some(account) = self.child_at(&path[..depth]) {
+                account.update_vec(&keys);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::account::{Account, Change, Patch, Valid};
+
+    fn account() -> Account<String, &'static str, i32> {
+        Account::new(
+            "Main".to_string(),
+            true,
+            HashMap::from([("main", 0)]),
+            vec![
+                Account::new("Default".to_string(), true, [("lines", 3)].into(), vec![]),
+                Account::new(
+                    "Local".to_string(),
+                    true,
+                    HashMap::new(),
+                    vec![
+                        Account::new("Project".to_string(), true, [("words", 2)].into(), vec![]),
+                        Account::new("Old".to_string(), true, HashMap::new(), vec![]),
+                    ],
+                ),
+                Account::new("Removed".to_string(), true, [("ratio", 1)].into(), vec![]),
+            ],
+        )
+    }
+
+    #[test]
+    fn diff_round_trip() {
+        let old = account();
+        let mut new = account();
+        new.insert("main", 1);
+        new.deep_remove(&"lines", &mut vec![&"Default".to_string()])
+            .unwrap();
+        new.deep_insert(
+            &"ratio",
+            5,
+            &mut vec![&"Project".to_string(), &"Local".to_string()],
+        )
+        .unwrap();
+        new.deep_change_activity(false, &mut vec![&"Local".to_string()])
+            .unwrap();
+        new.deep_rename(
+            &"New".to_string(),
+            &mut vec![&"Old".to_string(), &"Local".to_string()],
+        )
+        .unwrap();
+        new.pop(Valid::new_true());
+        new.push(
+            Account::new(
+                "Added".to_string(),
+                true,
+                HashMap::new(),
+                vec![
+                    Account::new("Inner".to_string(), true, [("lines", 7)].into(), vec![]),
+                    Account::new("Other".to_string(), true, [("lines", 8)].into(), vec![]),
+                ],
+            ),
+            Valid::new_true(),
+        );
+        new.push(
+            Account::new("Last".to_string(), true, [("words", 9)].into(), vec![]),
+            Valid::new_true(),
+        );
+
+        let mut patched = account();
+        let conflicts = patched.apply_patch(Patch::from(old.diff(&new)));
+        assert_eq!(conflicts, vec![]);
+        assert_eq!(patched, new);
+        assert!(patched.diff(&new).is_empty());
+        // applying it again doesn't change anything
+        let conflicts = patched.apply_patch(Patch::from(old.diff(&new)));
+        assert_eq!(conflicts, vec![]);
+        assert_eq!(patched, new);
+    }
+    #[test]
+    fn reorder() {
+        let old = account();
+        let mut new = account();
+        let removed = new.pop(Valid::new_true()).unwrap();
+        let local = new.pop(Valid::new_true()).unwrap();
+        new.push(removed, Valid::new_true());
+        new.push(local, Valid::new_true());
+
+        let mut patched = account();
+        assert_eq!(patched.apply_patch(Patch::from(old.diff(&new))), vec![]);
+        assert_eq!(patched, new);
+    }
+    #[test]
+    fn conflicts() {
+        let patch = Patch::new(vec![
+            Change::Modified {
+                path: vec!["Default".to_string()],
+                key: "lines",
+                old: 1,
+                new: 2,
+            },
+            Change::Removed {
+                path: vec!["Missing".to_string()],
+                key: "lines",
+                value: 1,
+            },
+            Change::AccountRenamed {
+                path: vec![],
+                old: "Missing".to_string(),
+                new: "Other".to_string(),
+            },
+            Change::Added {
+                path: vec!["Removed".to_string()],
+                key: "ratio",
+                value: 2,
+            },
+        ]);
+        let mut account = account();
+        let conflicts = account.apply_patch(patch.clone());
+        assert_eq!(account, self::account());
+        assert_eq!(
+            conflicts
+                .iter()
+                .map(|conflict| (conflict.change(), conflict.found()))
+                .collect::<Vec<_>>(),
+            vec![
+                (&patch.changes()[0], Some(&3)),
+                (&patch.changes()[1], None),
+                (&patch.changes()[2], None),
+                (&patch.changes()[3], Some(&1)),
+            ]
+        );
+    }
+    #[test]
+    fn removed_accounts_keep_overrides() {
+        let old = account();
+        let mut new = account();
+        let (local, removed) = ("Local".to_string(), "Removed".to_string());
+        new.remove_account(&removed, Valid::new_true());
+        new.remove_account(&local, Valid::new_true());
+        let patch = Patch::from(old.diff(&new));
+
+        let mut account = account();
+        account
+            .deep_insert(&"ratio", 4, &mut vec![&removed])
+            .unwrap();
+        account
+            .deep_insert(&"lines", 5, &mut vec![&"Old".to_string(), &local])
+            .unwrap();
+        let conflicts = account.apply_patch(patch);
+
+        assert_eq!(account.get(&"ratio"), Some(&4));
+        assert_eq!(account.get(&"lines"), Some(&5));
+        assert_eq!(account.get(&"words"), None);
+        assert_eq!(
+            account.deep(&mut vec![&local]).unwrap().accounts_names(),
+            vec![&"Old".to_string()]
+        );
+        assert_eq!(
+            conflicts
+                .iter()
+                .map(|conflict| (conflict.change().to_string(), conflict.found()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("[\"Removed\"] - \"ratio\": 1".to_string(), Some(&4)),
+                ("[] - account \"Removed\" at 2".to_string(), None),
+                ("[\"Local\"] - account \"Old\" at 1".to_string(), None),
+                ("[] - account \"Local\" at 1".to_string(), None),
+            ]
+        );
+        assert!(account.valid().is_valid());
+    }
+    #[cfg(all(feature = "serde", feature = "json"))]
+    #[test]
+    fn serialized() {
+        let account = || {
+            Account::<String, String, i32>::new(
+                "Main".to_string(),
+                true,
+                HashMap::new(),
+                vec![Account::new(
+                    "Default".to_string(),
+                    true,
+                    [("lines".to_string(), 3)].into(),
+                    vec![],
+                )],
+            )
+        };
+        let mut new = account();
+        new.deep_insert(&"lines".to_string(), 5, &mut vec![&"Default".to_string()])
+            .unwrap();
+        new.push(
+            Account::new("Local".to_string(), false, HashMap::new(), vec![]),
+            Valid::new_true(),
+        );
+        let json = serde_json::to_string(&Patch::from(account().diff(&new))).unwrap();
+        let mut patched = account();
+        assert_eq!(
+            patched.apply_patch(serde_json::from_str(&json).unwrap()),
+            vec![]
+        );
+        assert_eq!(patched, new);
+    }
+}
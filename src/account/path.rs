@@ -0,0 +1,325 @@
+use core::{
+    fmt::{self, Display, Write},
+    mem::take,
+    str::FromStr,
+};
+use std::{slice, vec};
+
+/// Path to a [child](crate::account::Account#accounts) `Account`, from the `Account` itself to the child.
+///
+/// Accepted by all the [deep functions](crate::account::Account#deep-functions) through [`DeepNames`],
+/// and can be parsed from, or displayed as, a `str` with the names separated by `/`.
+/// A `/` or `\` in a name is escaped with a `\`, so every path is displayed as a `str` that parses back to it.
+///
+/// # Examples
+///
+/// ```
+/// use hashmap_settings::account::{Account,AccountPath};
+///
+/// let mut account = Account::<String,&str,i32>::new(
+///     "Main".to_string(),
+///     true,
+///     Default::default(),
+///     vec![Account::new("Default".to_string(), true, Default::default(), vec![
+///         Account::new("Editor".to_string(), true, [("lines", 3)].into(), vec![]),
+///     ])],
+/// );
+/// let path = "Default/Editor".parse::<AccountPath<String>>()?;
+/// account.deep_insert(&"lines", 4, &path).unwrap();
+/// assert_eq!(account.get(&"lines"), Some(&4));
+/// assert_eq!(path.to_string(), "Default/Editor");
+/// # Ok::<(), hashmap_settings::account::AccountPathError<core::convert::Infallible>>(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[allow(clippy::module_name_repetitions)]
+pub struct AccountPath<N> {
+    names: Vec<N>,
+}
+impl<N> AccountPath<N> {
+    /// Creates a new `AccountPath` from the names of the child `Accounts`, starting with the direct child.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::AccountPath;
+    ///
+    /// let path = AccountPath::new(vec!["Default", "Editor"]);
+    /// assert_eq!(path.names(), &["Default", "Editor"]);
+    /// ```
+    #[must_use]
+    pub const fn new(names: Vec<N>) -> Self {
+        Self { names }
+    }
+    /// Returns the names in the path, starting with the direct child.
+    #[must_use]
+    pub fn names(&self) -> &[N] {
+        &self.names
+    }
+    /// Returns an iterator over the names in the path, starting with the direct child.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::AccountPath;
+    ///
+    /// let path = AccountPath::new(vec!["Default", "Editor"]);
+    /// assert_eq!(path.iter().collect::<Vec<_>>(), vec![&"Default", &"Editor"]);
+    /// ```
+    pub fn iter(&self) -> slice::Iter<'_, N> {
+        self.names.iter()
+    }
+    /// Returns the number of names in the path.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.names.len()
+    }
+    /// Returns `true` if the path contains no names.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+    /// Appends a name to the end of the path, making it point to a child of the current last `Account`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::AccountPath;
+    ///
+    /// let mut path = AccountPath::new(vec!["Default"]);
+    /// path.push("Editor");
+    /// assert_eq!(path, AccountPath::new(vec!["Default", "Editor"]));
+    /// ```
+    pub fn push(&mut self, name: N) {
+        self.names.push(name);
+    }
+    /// Removes the last name of the path and returns it, or [`None`] if it is empty.
+    pub fn pop(&mut self) -> Option<N> {
+        self.names.pop()
+    }
+}
+impl<N> From<Vec<N>> for AccountPath<N> {
+    fn from(names: Vec<N>) -> Self {
+        Self::new(names)
+    }
+}
+impl<N> FromIterator<N> for AccountPath<N> {
+    fn from_iter<T: IntoIterator<Item = N>>(iter: T) -> Self {
+        Self::new(iter.into_iter().collect())
+    }
+}
+impl<N> IntoIterator for AccountPath<N> {
+    type Item = N;
+    type IntoIter = vec::IntoIter<N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.names.into_iter()
+    }
+}
+impl<'a, N> IntoIterator for &'a AccountPath<N> {
+    type Item = &'a N;
+    type IntoIter = slice::Iter<'a, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+impl<N: Display> Display for AccountPath<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, name) in self.names.iter().enumerate() {
+            if index > 0 {
+                f.write_str("/")?;
+            }
+            for char in name.to_string().chars() {
+                if matches!(char, '/' | '\\') {
+                    f.write_char('\\')?;
+                }
+                f.write_char(char)?;
+            }
+        }
+        Ok(())
+    }
+}
+impl<N: FromStr> FromStr for AccountPath<N> {
+    type Err = AccountPathError<N::Err>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut names = vec![];
+        let mut name = String::new();
+        let mut chars = s.chars();
+        while let Some(char) = chars.next() {
+            match char {
+                '/' => names.push(parse_name(&take(&mut name))?),
+                '\\' => match chars.next() {
+                    Some(escaped @ ('/' | '\\')) => name.push(escaped),
+                    _ => return Err(AccountPathError::Escape),
+                },
+                _ => name.push(char),
+            }
+        }
+        names.push(parse_name(&name)?);
+        Ok(Self::new(names))
+    }
+}
+fn parse_name<N: FromStr>(name: &str) -> Result<N, AccountPathError<N::Err>> {
+    if name.is_empty() {
+        Err(AccountPathError::EmptyName)
+    } else {
+        name.parse().map_err(AccountPathError::Name)
+    }
+}
+
+/// Errors of parsing an [`AccountPath`]
+#[derive(Debug, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub enum AccountPathError<E> {
+    /// Error of a path with an empty name, like `""` or `"Default//Colors"`
+    EmptyName,
+    /// Error of a `\` that isn't followed by a `/` or another `\`
+    Escape,
+    /// Error of a name that couldn't be parsed
+    Name(E),
+}
+impl<E: Display> Display for AccountPathError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyName => f.write_str("account path contains an empty name"),
+            Self::Escape => f.write_str("account path contains an invalid escape"),
+            Self::Name(error) => error.fmt(f),
+        }
+    }
+}
+impl<E: fmt::Debug + Display> std::error::Error for AccountPathError<E> {}
+
+/// Names of a child `Account` accepted by the [deep functions](crate::account::Account#deep-functions)
+///
+/// Implemented for an [`AccountPath`], and for a `Vec` of &N with the names in reverse order,
+/// where the right-most element is the direct child, which is emptied by the deep function,
+/// even if it returns an error.
+pub trait DeepNames<'a, N> {
+    /// Returns the names in reverse order, with the direct child as the last element.
+    fn deep_names(self) -> Vec<&'a N>;
+}
+impl<'a, N> DeepNames<'a, N> for &'a AccountPath<N> {
+    fn deep_names(self) -> Vec<&'a N> {
+        self.names.iter().rev().collect()
+    }
+}
+impl<'a, N> DeepNames<'a, N> for &mut Vec<&'a N> {
+    fn deep_names(self) -> Vec<&'a N> {
+        take(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, num::ParseIntError};
+
+    use crate::account::{Account, AccountPath, AccountPathError, DeepError, Valid};
+
+    #[test]
+    fn paths_parse_and_display() {
+        let path = "Default/Editor/Colors"
+            .parse::<AccountPath<String>>()
+            .unwrap();
+        assert_eq!(path.names(), &["Default", "Editor", "Colors"]);
+        assert_eq!(path.to_string(), "Default/Editor/Colors");
+        assert_eq!(
+            "1/2".parse::<AccountPath<u8>>(),
+            Ok(AccountPath::new(vec![1, 2]))
+        );
+        assert_eq!(
+            "Default//Colors".parse::<AccountPath<String>>(),
+            Err(AccountPathError::EmptyName)
+        );
+        assert!(matches!(
+            "1/x".parse::<AccountPath<u8>>(),
+            Err(AccountPathError::Name(ParseIntError { .. }))
+        ));
+        assert_eq!(
+            "".parse::<AccountPath<String>>(),
+            Err(AccountPathError::EmptyName)
+        );
+    }
+    #[test]
+    fn separators_in_names_are_escaped() {
+        let path = AccountPath::new(vec![
+            "Default".to_string(),
+            "a/b".to_string(),
+            "c\\d".to_string(),
+            "\\/".to_string(),
+        ]);
+        assert_eq!(path.to_string(), "Default/a\\/b/c\\\\d/\\\\\\/");
+        assert_eq!(path.to_string().parse(), Ok(path));
+        assert_eq!(
+            "Default/a\\b".parse::<AccountPath<String>>(),
+            Err(AccountPathError::Escape)
+        );
+        assert_eq!(
+            "Default\\".parse::<AccountPath<String>>(),
+            Err(AccountPathError::Escape)
+        );
+        assert_eq!(
+            "\\/".parse::<AccountPath<String>>(),
+            Ok(AccountPath::new(vec!["/".to_string()]))
+        );
+    }
+    #[test]
+    fn paths_and_vecs_in_deep_functions() {
+        let mut account = Account::<String, &str, i32>::new(
+            "Main".to_string(),
+            true,
+            HashMap::new(),
+            vec![Account::new(
+                "Default".to_string(),
+                true,
+                HashMap::new(),
+                vec![Account::new(
+                    "Editor".to_string(),
+                    true,
+                    [("lines", 3)].into(),
+                    vec![],
+                )],
+            )],
+        );
+        let path = "Default/Editor".parse::<AccountPath<String>>().unwrap();
+        let (default, editor) = ("Default".to_string(), "Editor".to_string());
+
+        assert_eq!(account.deep_insert(&"lines", 4, &path), Ok(Some(3)));
+        assert_eq!(
+            account.deep_insert(&"lines", 5, &mut vec![&editor, &default]),
+            Ok(Some(4))
+        );
+        assert_eq!(account.get(&"lines"), Some(&5));
+        assert_eq!(account.deep(&path).unwrap().name(), &editor);
+        assert_eq!(account.deep_change_activity(false, &path), Ok(true));
+        assert_eq!(account.get(&"lines"), None);
+        assert!(account.valid().is_valid());
+
+        let mut history = crate::account::History::new(account.clone());
+        assert_eq!(
+            history.deep_push(
+                Account::new("Colors".to_string(), true, HashMap::new(), vec![]),
+                Valid::new_true(),
+                &path,
+            ),
+            Ok(())
+        );
+        assert!(history.undo());
+        assert_eq!(history.account(), &account);
+
+        assert_eq!(
+            account.deep_remove(&"lines", &AccountPath::new(vec![])),
+            Err(DeepError::EmptyVec)
+        );
+        assert_eq!(
+            account.deep_remove(&"lines", &AccountPath::new(vec![editor.clone()])),
+            Err(DeepError::NotFound)
+        );
+        let mut names = vec![&default, &editor];
+        assert_eq!(
+            account.deep_remove(&"lines", &mut names),
+            Err(DeepError::NotFound)
+        );
+        assert!(names.is_empty());
+    }
+}
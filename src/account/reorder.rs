@@ -0,0 +1,646 @@
+use core::ops::Range;
+use std::{collections::HashSet, hash::Hash};
+
+use crate::account::{Account, DeepError, DeepNames, Incrementable, Valid};
+
+impl<N: Clone + Eq + Hash + Incrementable, K: Clone + Eq + Hash, V: Clone + PartialEq>
+    Account<N, K, V>
+{
+    /// Inserts an `Account` at position `index` within the `Vec` of child `Accounts`, shifting all the ones above it up.
+    ///
+    /// Only the settings of the inserted `Account` are updated, if it's [active](Account::active).
+    ///
+    /// Depending on the [Valid] provided it could make the parent `Account` [invalid](Account#valid).
+    /// Providing a `Valid::new_true()` will always result in a valid `Account` so it is recommended,
+    /// renaming the inserted `Account` if there's already a child `Account` with its name.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::{Account,Valid};
+    ///
+    /// let mut account = Account::<String,&str,i32>::new(
+    ///     "Main".to_string(),
+    ///     true,
+    ///     Default::default(),
+    ///     vec![
+    ///         Account::new("Default".to_string(), true, [("lines", 3), ("words", 2)].into(), vec![]),
+    ///         Account::new("User".to_string(), true, [("lines", 5)].into(), vec![]),
+    ///     ],
+    /// );
+    /// account.insert_account(
+    ///     1,
+    ///     Account::new("Project".to_string(), true, [("lines", 4), ("words", 4)].into(), vec![]),
+    ///     Valid::new_true(),
+    /// );
+    /// assert_eq!(account.accounts_names(), vec!["Default", "Project", "User"]);
+    /// assert_eq!(account.get(&"lines"), Some(&5));
+    /// assert_eq!(account.get(&"words"), Some(&4));
+    /// ```
+    pub fn insert_account(&mut self, index: usize, account: Self, valid: Valid) {
+        let keys = self.insert_account_keys(index, account, valid);
+        if valid.settings {
+            self.update_vec(&keys.iter().collect());
+        } else if !keys.is_empty() {
+            self.valid.settings = false;
+        }
+        self.evaluate_inserted_conditions();
+    }
+    /// Inserts an `Account` in the `Vec` of child `Accounts` of a child `Account`.
+    ///
+    /// Part of the [deep functions](Account#deep-functions) group that accept a `Vec` of &N to identify
+    /// the child `Account` to run the function. [`insert_account`](Account::insert_account) in this case.
+    ///
+    /// Also updates the settings in all the affected accounts such that they contain the correct values.
+    ///
+    /// # Errors
+    ///
+    /// Deep functions can return [`DeepError`]'s
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the number of child `Accounts` of the child `Account`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::{Account,Valid};
+    ///
+    /// let mut account = Account::<String,&str,i32>::new(
+    ///     "Main".to_string(),
+    ///     true,
+    ///     Default::default(),
+    ///     vec![Account::new("Project".to_string(), true, Default::default(), vec![
+    ///         Account::new("Shared".to_string(), true, [("lines", 3)].into(), vec![]),
+    ///     ])],
+    /// );
+    /// account.deep_insert_account(
+    ///     0,
+    ///     Account::new("Default".to_string(), true, [("lines", 1), ("words", 2)].into(), vec![]),
+    ///     Valid::new_true(),
+    ///     &mut vec![&"Project".to_string()],
+    /// )?;
+    /// assert_eq!(account.get(&"lines"), Some(&3));
+    /// assert_eq!(account.get(&"words"), Some(&2));
+    /// # Ok::<(), hashmap_settings::account::DeepError>(())
+    /// ```
+    pub fn deep_insert_account<'a>(
+        &mut self,
+        index: usize,
+        account: Self,
+        valid: Valid,
+        account_names: impl DeepNames<'a, N>,
+    ) -> Result<(), DeepError>
+    where
+        N: 'a,
+    {
+        let account_names = &mut account_names.deep_names();
+        self.deep_reorder(account_names, |found_account| {
+            found_account.insert_account(index, account, valid);
+            if valid.settings {
+                let keys = found_account.accounts[index].active_keys();
+                ((), keys)
+            } else {
+                ((), vec![])
+            }
+        })
+    }
+    /// Inserts the `Account` without updating the settings, returning the ones that need to be updated.
+    fn insert_account_keys(&mut self, index: usize, account: Self, valid: Valid) -> Vec<K> {
+        let mut account = account;
+        account.inherit_merge_policies(&self.merge);
+        if !account.valid.is_valid() {
+            if valid.children {
+                account.fix_valid(Valid::new_true());
+            } else {
+                self.valid.children = false;
+            }
+        }
+        if self.account_from_name(&account.name).is_some() {
+            if valid.names {
+                while self.account_from_name(&account.name).is_some() {
+                    account.name.increment_mut();
+                }
+            } else {
+                self.valid.names = false;
+            }
+        }
+        let keys = account.active_keys();
+        self.accounts.insert(index, account);
+        self.locks.clear();
+        keys
+    }
+    fn evaluate_inserted_conditions(&mut self) {
+        if self
+            .accounts
+            .iter()
+            .any(|account| account.condition.is_some())
+        {
+            // cycles are returned by `update_conditions`
+            let _ = self.evaluate_conditions();
+        }
+    }
+}
+impl<N: Eq + Hash, K: Clone + Eq + Hash, V: Clone + PartialEq> Account<N, K, V> {
+    /// Removes the child `Account` with the name provided and returns it, or [`None`] if there is none.
+    ///
+    /// Only the settings of the removed `Account` are updated, if it was [active](Account::active).
+    ///
+    /// Depending on the [Valid] provided it could make the parent `Account` [invalid](Account#valid).
+    /// Providing a `Valid::new_true()` will always result in a valid `Account` so it is recommended.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::{Account,Valid};
+    ///
+    /// let mut account = Account::<String,&str,i32>::new(
+    ///     "Main".to_string(),
+    ///     true,
+    ///     Default::default(),
+    ///     vec![
+    ///         Account::new("Default".to_string(), true, [("lines", 3)].into(), vec![]),
+    ///         Account::new("Project".to_string(), true, [("lines", 4)].into(), vec![]),
+    ///         Account::new("User".to_string(), true, [("words", 2)].into(), vec![]),
+    ///     ],
+    /// );
+    /// let removed = account.remove_account(&"Project".to_string(), Valid::new_true());
+    /// assert_eq!(removed.map(|account| account.name().clone()), Some("Project".to_string()));
+    /// assert_eq!(account.get(&"lines"), Some(&3));
+    /// assert!(account.remove_account(&"Project".to_string(), Valid::new_true()).is_none());
+    /// ```
+    pub fn remove_account(&mut self, account_name: &N, valid: Valid) -> Option<Self> {
+        let index = self.position(account_name)?;
+        let removed = self.accounts.remove(index);
+        self.locks.clear();
+        if !self.valid.names && valid.names {
+            self.valid.names = self.update_valid_names();
+        }
+        if !self.valid.children && valid.children {
+            self.valid.children = self.update_valid_children();
+        }
+        let keys = removed.active_keys();
+        if valid.settings {
+            self.update_vec(&keys.iter().collect());
+            if !self.valid.settings {
+                self.valid.settings = self.update_valid_settings();
+            }
+        } else if !keys.is_empty() {
+            self.valid.settings = false;
+        }
+        Some(removed)
+    }
+    /// Moves the child `Account` with the name provided to position `new_index`, returning `false` if there is none.
+    ///
+    /// Only the settings the moved `Account` shares with the `Accounts` it moved past are updated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `new_index >= len`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::Account;
+    ///
+    /// let mut account = Account::<String,&str,i32>::new(
+    ///     "Main".to_string(),
+    ///     true,
+    ///     Default::default(),
+    ///     vec![
+    ///         Account::new("Default".to_string(), true, [("lines", 3)].into(), vec![]),
+    ///         Account::new("Project".to_string(), true, [("lines", 4)].into(), vec![]),
+    ///         Account::new("User".to_string(), true, [("lines", 5)].into(), vec![]),
+    ///     ],
+    /// );
+    /// assert!(account.move_account(&"Project".to_string(), 2));
+    /// assert_eq!(account.accounts_names(), vec!["Default", "User", "Project"]);
+    /// assert_eq!(account.get(&"lines"), Some(&4));
+    /// ```
+    pub fn move_account(&mut self, account_name: &N, new_index: usize) -> bool {
+        self.move_account_keys(account_name, new_index)
+            .is_some_and(|keys| {
+                self.update_vec(&keys.iter().collect());
+                true
+            })
+    }
+    /// Swaps the child `Accounts` with the names provided, returning `false` if one of them doesn't exist.
+    ///
+    /// Only the settings the swapped `Accounts` share, with each other or with the `Accounts` between them, are updated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::Account;
+    ///
+    /// let mut account = Account::<String,&str,i32>::new(
+    ///     "Main".to_string(),
+    ///     true,
+    ///     Default::default(),
+    ///     vec![
+    ///         Account::new("Default".to_string(), true, [("lines", 3)].into(), vec![]),
+    ///         Account::new("Project".to_string(), true, [("words", 4)].into(), vec![]),
+    ///         Account::new("User".to_string(), true, [("lines", 5)].into(), vec![]),
+    ///     ],
+    /// );
+    /// assert!(account.swap_accounts(&"Default".to_string(), &"User".to_string()));
+    /// assert_eq!(account.accounts_names(), vec!["User", "Project", "Default"]);
+    /// assert_eq!(account.get(&"lines"), Some(&3));
+    /// ```
+    pub fn swap_accounts(&mut self, first_name: &N, second_name: &N) -> bool {
+        self.swap_accounts_keys(first_name, second_name)
+            .is_some_and(|keys| {
+                self.update_vec(&keys.iter().collect());
+                true
+            })
+    }
+    fn move_account_keys(&mut self, account_name: &N, new_index: usize) -> Option<Vec<K>> {
+        let index = self.position(account_name)?;
+        assert!(
+            new_index < self.len(),
+            "new_index (is {new_index}) should be < len (is {})",
+            self.len()
+        );
+        let keys = if index < new_index {
+            self.crossed_keys(index, index + 1..new_index + 1)
+        } else {
+            self.crossed_keys(index, new_index..index)
+        };
+        let account = self.accounts.remove(index);
+        self.accounts.insert(new_index, account);
+        Some(keys)
+    }
+    fn swap_accounts_keys(&mut self, first_name: &N, second_name: &N) -> Option<Vec<K>> {
+        let first = self.position(first_name)?;
+        let second = self.position(second_name)?;
+        let (low, high) = (first.min(second), first.max(second));
+        let mut keys = self.crossed_keys(low, low + 1..high + 1);
+        keys.extend(self.crossed_keys(high, low + 1..high));
+        self.accounts.swap(low, high);
+        Some(keys)
+    }
+    fn position(&self, account_name: &N) -> Option<usize> {
+        self.accounts
+            .iter()
+            .position(|account| &account.name == account_name)
+    }
+    /// Returns the settings of the child `Account` at `index` that are also in the active child `Accounts` in `others`,
+    /// which are the only ones that can change when their order changes.
+    fn crossed_keys(&self, index: usize, others: Range<usize>) -> Vec<K> {
+        if !self.accounts[index].active {
+            return vec![];
+        }
+        let others = self.accounts[others]
+            .iter()
+            .filter(|account| account.active)
+            .flat_map(Self::layer_keys)
+            .collect::<HashSet<_>>();
+        self.accounts[index]
+            .layer_keys()
+            .into_iter()
+            .filter(|key| others.contains(key))
+            .cloned()
+            .collect()
+    }
+    /// Removes a child `Account` from a child `Account` and returns it, or [`None`] if there is none.
+    ///
+    /// Part of the [deep functions](Account#deep-functions) group that accept a `Vec` of &N to identify
+    /// the child `Account` to run the function. [`remove_account`](Account::remove_account) in this case.
+    ///
+    /// Also updates the settings in all the affected accounts such that they contain the correct values.
+    ///
+    /// # Errors
+    ///
+    /// Deep functions can return [`DeepError`]'s
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::{Account,Valid};
+    ///
+    /// let mut account = Account::<String,&str,i32>::new(
+    ///     "Main".to_string(),
+    ///     true,
+    ///     Default::default(),
+    ///     vec![Account::new("Project".to_string(), true, Default::default(), vec![
+    ///         Account::new("Default".to_string(), true, [("lines", 3)].into(), vec![]),
+    ///         Account::new("Shared".to_string(), true, [("lines", 4)].into(), vec![]),
+    ///         Account::new("Local".to_string(), true, [("words", 2)].into(), vec![]),
+    ///     ])],
+    /// );
+    /// let removed = account.deep_remove_account(&"Shared".to_string(), Valid::new_true(), &mut vec![&"Project".to_string()])?;
+    /// assert!(removed.is_some());
+    /// assert_eq!(account.get(&"lines"), Some(&3));
+    /// # Ok::<(), hashmap_settings::account::DeepError>(())
+    /// ```
+    pub fn deep_remove_account<'a>(
+        &mut self,
+        account_name: &N,
+        valid: Valid,
+        account_names: impl DeepNames<'a, N>,
+    ) -> Result<Option<Self>, DeepError>
+    where
+        N: 'a,
+    {
+        let account_names = &mut account_names.deep_names();
+        self.deep_reorder(account_names, |found_account| {
+            let removed = found_account.remove_account(account_name, valid);
+            let keys = removed
+                .as_ref()
+                .filter(|_| valid.settings)
+                .map_or_else(Vec::new, Self::active_keys);
+            (removed, keys)
+        })
+    }
+    /// Moves a child `Account` of a child `Account` to position `new_index`, returning `false` if there is none.
+    ///
+    /// Part of the [deep functions](Account#deep-functions) group that accept a `Vec` of &N to identify
+    /// the child `Account` to run the function. [`move_account`](Account::move_account) in this case.
+    ///
+    /// Also updates the settings in all the affected accounts such that they contain the correct values.
+    ///
+    /// # Errors
+    ///
+    /// Deep functions can return [`DeepError`]'s
+    ///
+    /// # Panics
+    ///
+    /// Panics if `new_index` isn't less than the number of child `Accounts` of the child `Account`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::Account;
+    ///
+    /// let mut account = Account::<String,&str,i32>::new(
+    ///     "Main".to_string(),
+    ///     true,
+    ///     Default::default(),
+    ///     vec![Account::new("Project".to_string(), true, Default::default(), vec![
+    ///         Account::new("Shared".to_string(), true, [("lines", 4)].into(), vec![]),
+    ///         Account::new("Local".to_string(), true, [("lines", 5)].into(), vec![]),
+    ///     ])],
+    /// );
+    /// assert_eq!(account.deep_move_account(&"Shared".to_string(), 1, &mut vec![&"Project".to_string()]), Ok(true));
+    /// assert_eq!(account.get(&"lines"), Some(&4));
+    /// ```
+    pub fn deep_move_account<'a>(
+        &mut self,
+        account_name: &N,
+        new_index: usize,
+        account_names: impl DeepNames<'a, N>,
+    ) -> Result<bool, DeepError>
+    where
+        N: 'a,
+    {
+        let account_names = &mut account_names.deep_names();
+        self.deep_reorder(account_names, |found_account| {
+            found_account
+                .move_account_keys(account_name, new_index)
+                .map_or_else(
+                    || (false, vec![]),
+                    |keys| {
+                        found_account.update_vec(&keys.iter().collect());
+                        (true, keys)
+                    },
+                )
+        })
+    }
+    /// Swaps two child `Accounts` of a child `Account`, returning `false` if one of them doesn't exist.
+    ///
+    /// Part of the [deep functions](Account#deep-functions) group that accept a `Vec` of &N to identify
+    /// the child `Account` to run the function. [`swap_accounts`](Account::swap_accounts) in this case.
+    ///
+    /// Also updates the settings in all the affected accounts such that they contain the correct values.
+    ///
+    /// # Errors
+    ///
+    /// Deep functions can return [`DeepError`]'s
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::Account;
+    ///
+    /// let mut account = Account::<String,&str,i32>::new(
+    ///     "Main".to_string(),
+    ///     true,
+    ///     Default::default(),
+    ///     vec![Account::new("Project".to_string(), true, Default::default(), vec![
+    ///         Account::new("Shared".to_string(), true, [("lines", 4)].into(), vec![]),
+    ///         Account::new("Local".to_string(), true, [("lines", 5)].into(), vec![]),
+    ///     ])],
+    /// );
+    /// assert_eq!(
+    ///     account.deep_swap_accounts(&"Shared".to_string(), &"Local".to_string(), &mut vec![&"Project".to_string()]),
+    ///     Ok(true)
+    /// );
+    /// assert_eq!(account.get(&"lines"), Some(&4));
+    /// ```
+    pub fn deep_swap_accounts<'a>(
+        &mut self,
+        first_name: &N,
+        second_name: &N,
+        account_names: impl DeepNames<'a, N>,
+    ) -> Result<bool, DeepError>
+    where
+        N: 'a,
+    {
+        let account_names = &mut account_names.deep_names();
+        self.deep_reorder(account_names, |found_account| {
+            found_account
+                .swap_accounts_keys(first_name, second_name)
+                .map_or_else(
+                    || (false, vec![]),
+                    |keys| {
+                        found_account.update_vec(&keys.iter().collect());
+                        (true, keys)
+                    },
+                )
+        })
+    }
+}
+impl<N: PartialEq, K: Clone + Eq + Hash, V: Clone> Account<N, K, V> {
+    /// Runs `change` on a child `Account`, then updates the settings it returns in all the `Accounts` in the path.
+    fn deep_reorder<T>(
+        &mut self,
+        account_names: &mut Vec<&N>,
+        change: impl FnOnce(&mut Self) -> (T, Vec<K>),
+    ) -> Result<T, DeepError> {
+        self.deep_reorder_helper(account_names, change)
+            .map(|(result, _)| result)
+    }
+    fn deep_reorder_helper<T>(
+        &mut self,
+        account_names: &mut Vec<&N>,
+        change: impl FnOnce(&mut Self) -> (T, Vec<K>),
+    ) -> Result<(T, Vec<K>), DeepError> {
+        let Some(account_to_find) = account_names.pop() else {
+            return Err(DeepError::EmptyVec);
+        };
+        let Some(found_account) = self.mut_account_from_name(account_to_find) else {
+            return Err(DeepError::NotFound);
+        };
+        let (result, keys) = if account_names.is_empty() {
+            change(found_account)
+        } else {
+            found_account.deep_reorder_helper(account_names, change)?
+        };
+        if found_account.active {
+            self.update_vec(&keys.iter().collect());
+        }
+        Ok((result, keys))
+    }
+}
+impl<N, K: Clone + Eq + Hash, V> Account<N, K, V> {
+    /// Returns the settings a parent `Account` needs to update when the `Account` is added or removed.
+    fn active_keys(&self) -> Vec<K> {
+        if self.active {
+            self.layer_keys().into_iter().cloned().collect()
+        } else {
+            vec![]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::account::{Account, DeepError, Valid};
+
+    fn layers(names: &[&str]) -> Vec<Account<String, &'static str, i32>> {
+        names
+            .iter()
+            .map(|name| match *name {
+                "Default" => Account::new(
+                    "Default".to_string(),
+                    true,
+                    [("lines", 1), ("words", 1), ("width", 1)].into(),
+                    vec![],
+                ),
+                "Shared" => Account::new(
+                    "Shared".to_string(),
+                    true,
+                    [("lines", 2), ("words", 2)].into(),
+                    vec![],
+                ),
+                "Local" => Account::new("Local".to_string(), true, [("lines", 3)].into(), vec![]),
+                "Off" => Account::new("Off".to_string(), false, [("width", 4)].into(), vec![]),
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+    fn account(names: &[&str]) -> Account<String, &'static str, i32> {
+        Account::new(
+            "Main".to_string(),
+            true,
+            HashMap::new(),
+            vec![Account::new(
+                "Project".to_string(),
+                true,
+                HashMap::new(),
+                layers(names),
+            )],
+        )
+    }
+
+    #[test]
+    fn reordered_accounts_match_new_accounts() {
+        let project = "Project".to_string();
+        let name = |name: &str| name.to_string();
+        let mut account = account(&["Default", "Local"]);
+
+        account
+            .deep_insert_account(
+                1,
+                layers(&["Shared"]).remove(0),
+                Valid::new_true(),
+                &mut vec![&project],
+            )
+            .unwrap();
+        assert_eq!(account, self::account(&["Default", "Shared", "Local"]));
+        account
+            .deep_insert_account(
+                0,
+                layers(&["Off"]).remove(0),
+                Valid::new_true(),
+                &mut vec![&project],
+            )
+            .unwrap();
+        assert_eq!(
+            account,
+            self::account(&["Off", "Default", "Shared", "Local"])
+        );
+
+        assert_eq!(
+            account.deep_move_account(&name("Local"), 1, &mut vec![&project]),
+            Ok(true)
+        );
+        assert_eq!(
+            account,
+            self::account(&["Off", "Local", "Default", "Shared"])
+        );
+        assert_eq!(account.get(&"lines"), Some(&2));
+        assert_eq!(
+            account.deep_move_account(&name("Other"), 1, &mut vec![&project]),
+            Ok(false)
+        );
+
+        assert_eq!(
+            account.deep_swap_accounts(&name("Off"), &name("Shared"), &mut vec![&project]),
+            Ok(true)
+        );
+        assert_eq!(
+            account,
+            self::account(&["Shared", "Local", "Default", "Off"])
+        );
+        assert_eq!(account.get(&"words"), Some(&1));
+
+        let removed = account
+            .deep_remove_account(&name("Default"), Valid::new_true(), &mut vec![&project])
+            .unwrap();
+        assert_eq!(removed, Some(layers(&["Default"]).remove(0)));
+        assert_eq!(account, self::account(&["Shared", "Local", "Off"]));
+        assert_eq!(account.get(&"width"), None);
+        assert_eq!(
+            account.deep_remove_account(
+                &name("Default"),
+                Valid::new_true(),
+                &mut vec![&name("Other")]
+            ),
+            Err(DeepError::NotFound)
+        );
+    }
+    #[test]
+    fn inserted_accounts_keep_valid() {
+        let mut account = account(&["Default"]);
+        let project = account.accounts()[0].clone();
+
+        account.insert_account(0, project.clone(), Valid::new_true());
+        assert_eq!(account.accounts_names(), vec!["Project(1)", "Project"]);
+        assert!(account.valid().is_valid());
+
+        account.insert_account(0, project, Valid::new(false, true, true));
+        assert_eq!(account.valid(), &Valid::new(false, true, true));
+        assert!(
+            account
+                .remove_account(&"Project".to_string(), Valid::new_true())
+                .is_some()
+        );
+        assert!(account.valid().is_valid());
+
+        account.insert_account(
+            2,
+            layers(&["Shared"]).remove(0),
+            Valid::new(true, false, true),
+        );
+        assert!(!account.valid().settings());
+        account.fix_valid(Valid::new_true());
+        assert_eq!(account.get(&"lines"), Some(&2));
+    }
+}
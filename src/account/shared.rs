@@ -0,0 +1,286 @@
+use core::fmt::Debug;
+use std::{
+    hash::Hash,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use arc_swap::ArcSwap;
+
+use crate::account::{Account, Snapshot};
+
+/// An [`Account`] that can be shared between threads
+///
+/// Writes are made with [`write`](SharedAccount::write) through the usual `Account` API, one at a time,
+/// and each one publishes a new [`Snapshot`]. Reads are made on the last published `Snapshot`, returned by
+/// [`load`](SharedAccount::load), so readers always see a consistent state and never wait for a write in progress:
+/// `Snapshot`s are published with an atomic swap, and loading one doesn't take any lock.
+///
+/// If a write panics, the changes it made are discarded, and the `Account` is restored to the last published `Snapshot`.
+///
+/// As `Snapshot`s share their structure, publishing one only copies the `Accounts` changed by the write.
+///
+/// A `SharedAccount` can only be shared between threads when `N`, `K` and `V` are [`Send`] and [`Sync`].
+/// [`Stg`](crate::stg::Stg) isn't, so use [`SendStg`](crate::stg::SendStg) to store values of different types.
+///
+/// Requires the "shared" feature.
+///
+/// # Examples
+///
+/// ```
+/// use std::{sync::Arc, thread};
+/// use hashmap_settings::{account::{Account,SharedAccount},stg::{SendStg,StgTrait}};
+///
+/// let shared = Arc::new(SharedAccount::new(Account::<String,&str,SendStg>::new(
+///     "Main".to_string(),
+///     true,
+///     Default::default(),
+///     vec![Account::new("Default".to_string(), true, [("lines", SendStg::new(3))].into(), vec![])],
+/// )));
+///
+/// let reader = {
+///     let shared = Arc::clone(&shared);
+///     thread::spawn(move || shared.load().get(&"lines").unstg::<i32>())
+/// };
+/// shared.write(|account| account.deep_insert(&"lines", SendStg::new(5), &mut vec![&"Default".to_string()]))?;
+///
+/// assert!([Ok(3), Ok(5)].contains(&reader.join().unwrap()));
+/// assert_eq!(shared.load().get(&"lines").unstg::<i32>(), Ok(5));
+/// # Ok::<(), hashmap_settings::account::DeepError>(())
+/// ```
+pub struct SharedAccount<N, K, V> {
+    account: Mutex<Account<N, K, V>>,
+    published: ArcSwap<Snapshot<N, K, V>>,
+}
+impl<N: Clone + PartialEq, K: Clone + Eq + Hash, V: Clone> SharedAccount<N, K, V> {
+    /// Creates a `SharedAccount`, publishing the first [`Snapshot`]
+    pub fn new(account: Account<N, K, V>) -> Self {
+        let published = ArcSwap::from_pointee(account.snapshot());
+        Self {
+            account: Mutex::new(account),
+            published,
+        }
+    }
+    /// Returns the last published [`Snapshot`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::{Account,SharedAccount};
+    ///
+    /// let shared = SharedAccount::new(Account::<(),&str,i32>::default());
+    /// let before = shared.load();
+    /// shared.write(|account| account.insert("lines", 3));
+    ///
+    /// assert_eq!(before.get(&"lines"), None);
+    /// assert_eq!(shared.load().get(&"lines"), Some(&3));
+    /// ```
+    #[must_use]
+    pub fn load(&self) -> Snapshot<N, K, V> {
+        Snapshot::clone(&self.published.load())
+    }
+    /// Changes the `Account` and publishes a new [`Snapshot`], returning the value returned by the closure
+    ///
+    /// Writes are made one at a time, and readers only see the `Account` once the closure returns,
+    /// so multiple changes made in the same closure are seen together.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::{Account,SharedAccount};
+    ///
+    /// let shared = SharedAccount::new(Account::<(),&str,i32>::default());
+    /// let old = shared.write(|account| {
+    ///     account.insert("lines", 3);
+    ///     account.insert("lines", 5)
+    /// });
+    /// assert_eq!(old, Some(3));
+    /// assert_eq!(shared.load().get(&"lines"), Some(&5));
+    /// ```
+    pub fn write<T, F: FnOnce(&mut Account<N, K, V>) -> T>(&self, f: F) -> T {
+        let mut account = self.lock();
+        let result = f(&mut account);
+        self.published.store(Arc::new(account.snapshot()));
+        // only released after publishing, so `Snapshot`s are published in the order of the writes
+        drop(account);
+        result
+    }
+    /// Returns the `Account`, consuming the `SharedAccount`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::{Account,SharedAccount};
+    ///
+    /// let shared = SharedAccount::new(Account::<(),&str,i32>::default());
+    /// shared.write(|account| account.insert("lines", 3));
+    /// assert_eq!(shared.into_inner().get(&"lines"), Some(&3));
+    /// ```
+    pub fn into_inner(self) -> Account<N, K, V> {
+        let published = self.load();
+        self.account.into_inner().unwrap_or_else(|poisoned| {
+            let mut account = poisoned.into_inner();
+            account.restore(&published);
+            account
+        })
+    }
+    /// Locks the `Account`, discarding the changes of a write that panicked.
+    fn lock(&self) -> MutexGuard<'_, Account<N, K, V>> {
+        self.account.lock().unwrap_or_else(|poisoned| {
+            let mut account = poisoned.into_inner();
+            account.restore(&self.load());
+            self.account.clear_poison();
+            account
+        })
+    }
+}
+impl<N: Debug, K: Debug, V: Debug> Debug for SharedAccount<N, K, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SharedAccount")
+            .field("published", &**self.published.load())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        panic::{AssertUnwindSafe, catch_unwind},
+        sync::Arc,
+        thread,
+    };
+
+    use crate::{
+        account::{Account, SharedAccount},
+        stg::{SendStg, StgTrait},
+    };
+
+    #[test]
+    fn readers_see_consistent_snapshots() {
+        const WRITES: i32 = 2000;
+        let shared = SharedAccount::new(Account::<String, &str, i32>::new(
+            "Main".to_string(),
+            true,
+            HashMap::new(),
+            vec![
+                Account::new("Default".to_string(), true, [("a", 0)].into(), vec![]),
+                Account::new("Local".to_string(), true, [("b", 0)].into(), vec![]),
+            ],
+        ));
+        let (default, local) = ("Default".to_string(), "Local".to_string());
+
+        thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    let mut last = 0;
+                    while last < WRITES {
+                        let snapshot = shared.load();
+                        let a = *snapshot.get(&"a").unwrap();
+                        let b = *snapshot.get(&"b").unwrap();
+                        // both settings are changed in the same write
+                        assert_eq!(a, b);
+                        assert!(a >= last);
+                        last = a;
+                    }
+                });
+            }
+            scope.spawn(|| {
+                for i in 1..=WRITES {
+                    shared.write(|account| {
+                        account.deep_insert(&"a", i, &mut vec![&default]).unwrap();
+                        account.deep_insert(&"b", i, &mut vec![&local]).unwrap();
+                    });
+                }
+            });
+        });
+
+        let account = shared.into_inner();
+        assert_eq!(account.get(&"a"), Some(&WRITES));
+        assert_eq!(account.get(&"b"), Some(&WRITES));
+    }
+    #[test]
+    fn stg_values_are_read_from_other_threads() {
+        const WRITES: i32 = 500;
+        let shared = Arc::new(SharedAccount::new(Account::<String, &str, SendStg>::new(
+            "Main".to_string(),
+            true,
+            HashMap::new(),
+            vec![
+                Account::new(
+                    "Default".to_string(),
+                    true,
+                    [("theme", SendStg::new("0".to_string()))].into(),
+                    vec![],
+                ),
+                Account::new(
+                    "Local".to_string(),
+                    true,
+                    [("lines", SendStg::new(0))].into(),
+                    vec![],
+                ),
+            ],
+        )));
+
+        let readers = (0..4)
+            .map(|_| {
+                let shared = Arc::clone(&shared);
+                thread::spawn(move || {
+                    let mut last = 0;
+                    while last < WRITES {
+                        let snapshot = shared.load();
+                        let lines = snapshot.get(&"lines").unstg::<i32>().unwrap();
+                        let theme = snapshot.get(&"theme").unstg::<String>().unwrap();
+                        // both settings are changed in the same write
+                        assert_eq!(theme, lines.to_string());
+                        assert!(lines >= last);
+                        last = lines;
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        let (default, local) = ("Default".to_string(), "Local".to_string());
+        for i in 1..=WRITES {
+            shared.write(|account| {
+                account
+                    .deep_insert(&"theme", SendStg::new(i.to_string()), &mut vec![&default])
+                    .unwrap();
+                account
+                    .deep_insert(&"lines", SendStg::new(i), &mut vec![&local])
+                    .unwrap();
+            });
+        }
+        for reader in readers {
+            reader.join().unwrap();
+        }
+        assert_eq!(shared.load().get(&"lines").unstg::<i32>(), Ok(WRITES));
+    }
+    #[test]
+    fn panicked_writes_are_discarded() {
+        let shared = SharedAccount::new(Account::<String, &str, i32>::new(
+            "Main".to_string(),
+            true,
+            HashMap::new(),
+            vec![Account::new(
+                "Default".to_string(),
+                true,
+                [("a", 0)].into(),
+                vec![],
+            )],
+        ));
+        let default = "Default".to_string();
+
+        let panicked = catch_unwind(AssertUnwindSafe(|| {
+            shared.write(|account| {
+                account.deep_insert(&"a", 1, &mut vec![&default]).unwrap();
+                panic!("write failed after a change");
+            });
+        }));
+        assert!(panicked.is_err());
+        assert_eq!(shared.load().get(&"a"), Some(&0));
+
+        shared.write(|account| account.insert("b", 2));
+        assert_eq!(shared.load().get(&"a"), Some(&0));
+        assert_eq!(shared.load().get(&"b"), Some(&2));
+        assert_eq!(shared.into_inner().get(&"a"), Some(&0));
+    }
+}
@@ -0,0 +1,526 @@
+use core::fmt::Debug;
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use crate::account::{
+    Account, Condition, Diff, Valid, diff::Tree, lock::LockCache, merge::MergePolicies, same_keys,
+    subscriptions::Subscriptions,
+};
+
+/// An immutable copy of an [`Account`] tree
+///
+/// Created by [`Account::snapshot`], it can be read, compared with [`diff`](Snapshot::diff),
+/// and used to [`restore`](Account::restore) an `Account` to the state it had.
+///
+/// Snapshots share their structure: each `Account` keeps the last `Snapshot` taken of it, so taking a new one
+/// only copies the settings of the `Accounts` that changed since, and shares every other child `Snapshot`.
+/// Cloning a `Snapshot` is only a reference count increment.
+///
+/// # Examples
+///
+/// ```
+/// use hashmap_settings::account::Account;
+///
+/// let mut account = Account::<String,&str,i32>::new(
+///     "Main".to_string(),
+///     true,
+///     Default::default(),
+///     vec![
+///         Account::new("Default".to_string(), true, [("lines", 3)].into(), vec![]),
+///         Account::new("Local".to_string(), true, [("words", 2)].into(), vec![]),
+///     ],
+/// );
+/// let before = account.snapshot();
+/// account.deep_insert(&"words", 4, &mut vec![&"Local".to_string()])?;
+/// let after = account.snapshot();
+///
+/// assert_eq!(before.get(&"words"), Some(&2));
+/// assert_eq!(after.get(&"words"), Some(&4));
+/// // "Default" didn't change so it's shared
+/// assert!(before.accounts()[0].ptr_eq(&after.accounts()[0]));
+///
+/// account.restore(&before);
+/// assert_eq!(account.get(&"words"), Some(&2));
+/// # Ok::<(), hashmap_settings::account::DeepError>(())
+/// ```
+pub struct Snapshot<N, K, V> {
+    node: Arc<Node<N, K, V>>,
+}
+/// An `Account` in a `Snapshot`
+struct Node<N, K, V> {
+    name: N,
+    active: bool,
+    /// The settings with the values they had in the `Account`, including the ones from its children.
+    settings: HashMap<K, V>,
+    /// The settings the `Account` defines itself, that aren't in any of its active children.
+    defined: HashMap<K, V>,
+    unset: Vec<K>,
+    locked: Vec<K>,
+    /// The settings locked in the `Account` or in any of its active children.
+    locks: HashSet<K>,
+    merge: MergePolicies<K, V>,
+    condition: Option<Condition<K, V>>,
+    accounts: Vec<Snapshot<N, K, V>>,
+    valid: Valid,
+}
+
+/// The last `Snapshot` taken of an `Account`, with the version of the settings it was taken at.
+pub(super) struct SnapshotCache<N, K, V> {
+    cached: Mutex<Option<Cached<N, K, V>>>,
+}
+type Cached<N, K, V> = (u64, Snapshot<N, K, V>);
+impl<N, K, V> SnapshotCache<N, K, V> {
+    pub(super) const fn new() -> Self {
+        Self {
+            cached: Mutex::new(None),
+        }
+    }
+    /// Forgets the cached `Snapshot`, for changes that aren't counted in the version of the settings.
+    pub(super) fn clear(&mut self) {
+        *self
+            .cached
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner) = None;
+    }
+}
+impl<N, K, V> Default for SnapshotCache<N, K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N, K, V> Snapshot<N, K, V> {
+    /// Returns the name of the `Account`
+    #[must_use]
+    pub fn name(&self) -> &N {
+        &self.node.name
+    }
+    /// Returns `true` if the `Account` was active
+    #[must_use]
+    pub fn active(&self) -> bool {
+        self.node.active
+    }
+    /// Returns the `Snapshot`s of the child `Accounts`
+    #[must_use]
+    pub fn accounts(&self) -> &[Self] {
+        &self.node.accounts
+    }
+    /// Returns `true` if both `Snapshot`s are the same, sharing their contents
+    ///
+    /// `Snapshot`s that aren't the same can still be equal.
+    #[must_use]
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.node, &other.node)
+    }
+}
+impl<N, K: Eq + Hash, V> Snapshot<N, K, V> {
+    /// Returns the value the setting had in the `Account`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::Account;
+    ///
+    /// let account = Account::<String,&str,i32>::new(
+    ///     "Main".to_string(),
+    ///     true,
+    ///     [("lines", 1), ("words", 1)].into(),
+    ///     vec![Account::new("Default".to_string(), true, [("lines", 3)].into(), vec![])],
+    /// );
+    /// let snapshot = account.snapshot();
+    /// assert_eq!(snapshot.get(&"lines"), Some(&3));
+    /// assert_eq!(snapshot.get(&"words"), Some(&1));
+    /// assert_eq!(snapshot.get(&"ratio"), None);
+    /// ```
+    #[must_use]
+    pub fn get(&self, setting_name: &K) -> Option<&V> {
+        self.node.settings.get(setting_name)
+    }
+    /// Returns `true` if the setting was unset, like [`Account::is_unset`]
+    #[must_use]
+    pub fn is_unset(&self, setting_name: &K) -> bool {
+        if self.get(setting_name).is_some() {
+            return false;
+        }
+        // as there's no value, the first child `Account` that is unset is the one the setting would be taken from
+        self.layers(setting_name)
+            .any(|account| account.is_unset(setting_name))
+            || self.node.unset.contains(setting_name)
+    }
+    /// Returns `true` if the setting was locked, like [`Account::is_locked`]
+    #[must_use]
+    pub fn is_locked(&self, setting_name: &K) -> bool {
+        self.node.locks.contains(setting_name)
+    }
+    /// Returns the active child `Snapshots` the value of a setting can be taken from, from the lowest to the highest.
+    fn layers(&self, setting: &K) -> impl DoubleEndedIterator<Item = &Self> {
+        let accounts = &self.node.accounts;
+        let end = accounts
+            .iter()
+            .position(|account| account.node.active && account.is_locked(setting))
+            .map_or(accounts.len(), |locked| locked + 1);
+        accounts[..end].iter().filter(|account| account.node.active)
+    }
+}
+impl<N: PartialEq, K: Eq + Hash, V: PartialEq> Snapshot<N, K, V> {
+    /// Returns the differences between `self` and `other`
+    ///
+    /// Works like [`Account::diff`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::{Account,Change};
+    ///
+    /// let mut account = Account::<String,&str,i32>::default();
+    /// account.insert("lines", 3);
+    /// let before = account.snapshot();
+    /// account.insert("lines", 5);
+    ///
+    /// assert_eq!(
+    ///     before.diff(&account.snapshot()).changes(),
+    ///     &vec![Change::Modified { path: vec![], key: &"lines", old: &3, new: &5 }]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn diff<'a>(&'a self, other: &'a Self) -> Diff<'a, N, K, V> {
+        Diff::new(&*self.node, &*other.node)
+    }
+}
+impl<N: Clone, K: Clone + Eq + Hash, V: Clone> Snapshot<N, K, V> {
+    /// Returns an `Account` with the state of the `Snapshot`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::Account;
+    ///
+    /// let account = Account::<String,&str,i32>::new(
+    ///     "Main".to_string(),
+    ///     true,
+    ///     Default::default(),
+    ///     vec![Account::new("Default".to_string(), true, [("lines", 3)].into(), vec![])],
+    /// );
+    /// assert_eq!(account.snapshot().to_account(), account);
+    /// ```
+    pub fn to_account(&self) -> Account<N, K, V> {
+        let accounts = self
+            .node
+            .accounts
+            .iter()
+            .map(Self::to_account)
+            .collect::<Vec<_>>();
+        Account {
+            name: self.node.name.clone(),
+            active: self.node.active,
+            settings: self.node.settings.clone(),
+            unset: self.node.unset.clone(),
+            locked: self.node.locked.clone(),
+            accounts,
+            valid: self.node.valid,
+            subscriptions: Subscriptions::new(),
+            // the new `Account` is already up to date with this `Snapshot`
+            locks: LockCache::new(),
+            snapshot: SnapshotCache {
+                cached: Mutex::new(Some((0, self.clone()))),
+            },
+            merge: self.node.merge.clone(),
+            condition: self.node.condition.clone(),
+            pending: Vec::new(),
+        }
+    }
+}
+impl<N, K, V> Clone for Snapshot<N, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            node: Arc::clone(&self.node),
+        }
+    }
+}
+impl<N: Debug, K: Debug, V: Debug> Debug for Snapshot<N, K, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Snapshot")
+            .field("name", &self.node.name)
+            .field("active", &self.node.active)
+            .field("settings", &self.node.settings)
+            .field("unset", &self.node.unset)
+            .field("locked", &self.node.locked)
+            .field("accounts", &self.node.accounts)
+            .field("valid", &self.node.valid)
+            .finish()
+    }
+}
+impl<N: PartialEq, K: Eq + Hash, V: PartialEq> PartialEq for Snapshot<N, K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr_eq(other)
+            || (self.node.name == other.node.name
+                && self.node.active == other.node.active
+                && self.node.settings == other.node.settings
+                && same_keys(&self.node.unset, &other.node.unset)
+                && same_keys(&self.node.locked, &other.node.locked)
+                && self.node.accounts == other.node.accounts
+                && self.node.valid == other.node.valid)
+    }
+}
+
+impl<N, K: Eq + Hash, V> Tree<N, K, V> for Node<N, K, V> {
+    fn name(&self) -> &N {
+        &self.name
+    }
+    fn active(&self) -> bool {
+        self.active
+    }
+    fn children(&self) -> usize {
+        self.accounts.len()
+    }
+    fn child(&self, index: usize) -> &Self {
+        &self.accounts[index].node
+    }
+    fn defined(&self, key: &K) -> Option<&V> {
+        self.defined.get(key)
+    }
+    fn defined_settings<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        self.defined.iter()
+    }
+}
+
+impl<N: Clone + PartialEq, K: Clone + Eq + Hash, V: Clone> Account<N, K, V> {
+    /// Returns an immutable [`Snapshot`] of the `Account` and its child `Accounts`
+    ///
+    /// Only the `Accounts` that changed since the last `Snapshot` are copied, so taking
+    /// a `Snapshot` after every change is cheap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::Account;
+    ///
+    /// let mut account = Account::<String,&str,i32>::default();
+    /// account.insert("lines", 3);
+    /// let snapshot = account.snapshot();
+    /// assert!(snapshot.ptr_eq(&account.snapshot()));
+    ///
+    /// account.insert("lines", 5);
+    /// assert_eq!(snapshot.get(&"lines"), Some(&3));
+    /// assert_eq!(account.snapshot().get(&"lines"), Some(&5));
+    /// ```
+    pub fn snapshot(&self) -> Snapshot<N, K, V> {
+        let accounts = self.accounts.iter().map(Self::snapshot).collect::<Vec<_>>();
+        let version = self.subscriptions.version();
+        let mut cached = self
+            .snapshot
+            .cached
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some((cached_version, snapshot)) = &*cached
+            && *cached_version == version
+            && snapshot.node.name == self.name
+            && snapshot.node.active == self.active
+            && snapshot.node.valid == self.valid
+            && snapshot.node.accounts.len() == accounts.len()
+            && snapshot
+                .node
+                .accounts
+                .iter()
+                .zip(&accounts)
+                .all(|(cached, account)| cached.ptr_eq(account))
+        {
+            return snapshot.clone();
+        }
+        let snapshot = Snapshot {
+            node: Arc::new(Node {
+                name: self.name.clone(),
+                active: self.active,
+                settings: self.settings.clone(),
+                defined: self
+                    .defined_settings()
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect(),
+                unset: self.unset.clone(),
+                locked: self.locked.clone(),
+                locks: self.subtree_locks().clone(),
+                merge: self.merge.clone(),
+                condition: self.condition.clone(),
+                accounts,
+                valid: self.valid,
+            }),
+        };
+        *cached = Some((version, snapshot.clone()));
+        snapshot
+    }
+    /// Restores the `Account` to the state of a [`Snapshot`]
+    ///
+    /// The subscriptions of the `Account` are kept, and are called for the settings that change value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::{Account,Valid};
+    ///
+    /// let mut account = Account::<String,&str,i32>::default();
+    /// account.insert("lines", 3);
+    /// let snapshot = account.snapshot();
+    ///
+    /// account.push(Account::new("Local".to_string(), true, [("lines", 5)].into(), vec![]), Valid::new_true());
+    /// account.insert("words", 2);
+    /// assert_eq!(account.get(&"lines"), Some(&5));
+    ///
+    /// account.restore(&snapshot);
+    /// assert_eq!(account.get(&"lines"), Some(&3));
+    /// assert_eq!(account.get(&"words"), None);
+    /// assert!(account.accounts().is_empty());
+    /// ```
+    pub fn restore(&mut self, snapshot: &Snapshot<N, K, V>) {
+        let restored = snapshot.to_account();
+        let removed = self
+            .settings
+            .keys()
+            .filter(|key| !restored.settings.contains_key(key))
+            .cloned()
+            .collect::<Vec<_>>();
+        for key in &removed {
+            self.subscriptions.remove(&mut self.settings, key);
+        }
+        for (key, value) in restored.settings {
+            self.subscriptions.insert(&mut self.settings, key, value);
+        }
+        self.unset = restored.unset;
+        self.locked = restored.locked;
+        self.name = restored.name;
+        self.active = restored.active;
+        self.accounts = restored.accounts;
+        self.locks.clear();
+        self.valid = restored.valid;
+        self.merge = restored.merge;
+        self.condition = restored.condition;
+        *self
+            .snapshot
+            .cached
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner) =
+            Some((self.subscriptions.version(), snapshot.clone()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{
+        account::{Account, Change, Valid},
+        stg::{Setting, Stg},
+    };
+
+    fn account() -> Account<String, String, Stg> {
+        Account::new(
+            "Main".to_string(),
+            true,
+            HashMap::new(),
+            vec![
+                Account::new(
+                    "Default".to_string(),
+                    true,
+                    [("lines".to_string(), 3.stg())].into(),
+                    vec![],
+                ),
+                Account::new(
+                    "Local".to_string(),
+                    true,
+                    HashMap::new(),
+                    vec![Account::new(
+                        "Project".to_string(),
+                        true,
+                        [("words".to_string(), 2.stg())].into(),
+                        vec![],
+                    )],
+                ),
+            ],
+        )
+    }
+
+    #[test]
+    fn structural_sharing() {
+        let mut account = account();
+        let (local, project) = ("Local".to_string(), "Project".to_string());
+        let first = account.snapshot();
+        assert!(first.ptr_eq(&account.snapshot()));
+
+        account
+            .deep_insert(&"words".to_string(), 4.stg(), &mut vec![&project, &local])
+            .unwrap();
+        let second = account.snapshot();
+        assert!(!first.ptr_eq(&second));
+        assert!(first.accounts()[0].ptr_eq(&second.accounts()[0]));
+        assert!(!first.accounts()[1].ptr_eq(&second.accounts()[1]));
+
+        account
+            .deep_change_activity(false, &mut vec![&"Default".to_string()])
+            .unwrap();
+        let third = account.snapshot();
+        assert!(second.accounts()[1].ptr_eq(&third.accounts()[1]));
+        assert_eq!(third.get(&"lines".to_string()), None);
+        assert_eq!(second.get(&"lines".to_string()), Some(&3.stg()));
+
+        account.push(
+            Account::new("Last".to_string(), true, HashMap::new(), vec![]),
+            Valid::new_true(),
+        );
+        assert_eq!(account.snapshot().accounts().len(), 3);
+        assert!(account.snapshot().accounts()[1].ptr_eq(&third.accounts()[1]));
+    }
+    #[test]
+    fn restore_and_diff() {
+        let mut account = account();
+        let first = account.snapshot();
+        account
+            .deep_insert(
+                &"words".to_string(),
+                4.stg(),
+                &mut vec![&"Project".to_string(), &"Local".to_string()],
+            )
+            .unwrap();
+        account.insert("ratio".to_string(), 1.stg());
+        let second = account.snapshot();
+        assert_eq!(
+            first.diff(&second).changes(),
+            &vec![
+                Change::Added {
+                    path: vec![],
+                    key: &"ratio".to_string(),
+                    value: &1.stg()
+                },
+                Change::Modified {
+                    path: vec![&"Local".to_string(), &"Project".to_string()],
+                    key: &"words".to_string(),
+                    old: &2.stg(),
+                    new: &4.stg()
+                },
+            ]
+        );
+
+        account.restore(&first);
+        assert_eq!(account, self::account());
+        assert!(account.snapshot().ptr_eq(&first));
+        account.restore(&second);
+        assert_eq!(account.snapshot(), second);
+        assert!(first.diff(&second.to_account().snapshot()) == first.diff(&second));
+    }
+    #[test]
+    fn reads_match_the_account() {
+        let mut account = account();
+        account.insert("lines".to_string(), 9.stg());
+        let snapshot = account.snapshot();
+        for key in ["lines", "words", "ratio"].map(String::from) {
+            assert_eq!(snapshot.get(&key), account.get(&key));
+        }
+        assert_eq!(snapshot.get(&"lines".to_string()), Some(&9.stg()));
+        assert_eq!(snapshot.to_account(), account);
+    }
+}
@@ -0,0 +1,345 @@
+use std::{
+    collections::{HashMap, hash_map::Entry},
+    hash::Hash,
+};
+
+use crate::account::Account;
+
+/// Called with the key, old value and new value of a changed setting, returning `false` to be unsubscribed.
+pub(super) type Callback<K, V> = Box<dyn FnMut(&K, Option<&V>, Option<&V>) -> bool + Send + Sync>;
+/// Returns `false` once the subscriber is gone, so it's removed without waiting for its setting to change.
+pub(super) type Open = Box<dyn Fn() -> bool + Send + Sync>;
+
+/// Identifier of a subscription to the changes of an [`Account`]'s settings
+///
+/// Returned by [`subscribe`](Account::subscribe) and [`subscribe_all`](Account::subscribe_all)
+/// and used to [`unsubscribe`](Account::unsubscribe).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SubscriptionId(u64);
+
+struct Subscriber<K, V> {
+    id: SubscriptionId,
+    key: Option<K>,
+    callback: Callback<K, V>,
+    open: Option<Open>,
+}
+impl<K, V> Subscriber<K, V> {
+    fn is_open(&self) -> bool {
+        self.open.as_ref().is_none_or(|open| open())
+    }
+}
+
+/// The subscribers of an `Account`, that are called when one of its settings changes value.
+///
+/// All changes to the settings of an `Account` go through [`insert`](Subscriptions::insert) and
+/// [`remove`](Subscriptions::remove), so that no change is missed. They are also counted in
+/// [`version`](Subscriptions::version), so a cached [`Snapshot`](crate::account::Snapshot) can tell if it's outdated.
+pub(super) struct Subscriptions<K, V> {
+    version: u64,
+    next_id: u64,
+    subscribers: Vec<Subscriber<K, V>>,
+    eq: Option<fn(&V, &V) -> bool>,
+}
+impl<K, V> Subscriptions<K, V> {
+    pub(super) const fn new() -> Self {
+        Self {
+            version: 0,
+            next_id: 0,
+            subscribers: Vec::new(),
+            eq: None,
+        }
+    }
+    pub(super) const fn version(&self) -> u64 {
+        self.version
+    }
+    #[cfg(all(test, feature = "async"))]
+    pub(super) const fn len(&self) -> usize {
+        self.subscribers.len()
+    }
+}
+impl<K: Eq + Hash, V> Subscriptions<K, V> {
+    pub(super) fn insert(&mut self, settings: &mut HashMap<K, V>, key: K, value: V) -> Option<V> {
+        self.version += 1;
+        if self.subscribers.is_empty() {
+            return settings.insert(key, value);
+        }
+        match settings.entry(key) {
+            Entry::Occupied(mut entry) => {
+                let old = entry.insert(value);
+                self.notify(entry.key(), Some(&old), Some(entry.get()));
+                Some(old)
+            }
+            Entry::Vacant(entry) => {
+                let entry = entry.insert_entry(value);
+                self.notify(entry.key(), None, Some(entry.get()));
+                None
+            }
+        }
+    }
+    pub(super) fn remove(&mut self, settings: &mut HashMap<K, V>, key: &K) -> Option<V> {
+        let (key, old) = settings.remove_entry(key)?;
+        self.version += 1;
+        self.notify(&key, Some(&old), None);
+        Some(old)
+    }
+    fn notify(&mut self, key: &K, old: Option<&V>, new: Option<&V>) {
+        if self.subscribers.is_empty() {
+            return;
+        }
+        if let (Some(old), Some(new), Some(eq)) = (old, new, self.eq)
+            && eq(old, new)
+        {
+            return;
+        }
+        self.subscribers.retain_mut(|subscriber| {
+            subscriber.is_open()
+                && (subscriber
+                    .key
+                    .as_ref()
+                    .is_some_and(|subscribed| subscribed != key)
+                    || (subscriber.callback)(key, old, new))
+        });
+    }
+}
+impl<K, V> Default for Subscriptions<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N, K: Eq + Hash, V: PartialEq> Account<N, K, V> {
+    /// Registers a callback that is called when the value of a setting changes
+    ///
+    /// The callback receives the key, the old value and the new value, where `None` means the setting
+    /// isn't present. It's only called when the value actually changes, so inserting a value equal to the
+    /// current one doesn't call it.
+    ///
+    /// Changes to the settings of the `Account` are caused by [`insert`](Account::insert) and [`remove`](Account::remove),
+    /// but also by changes to its child `Accounts`, like [`deep_insert`](Account::deep_insert),
+    /// [`deep_change_activity`](Account::deep_change_activity), [`push`](Account::push) or [`pop`](Account::pop),
+    /// so the callback is called whenever [`get`](Account::get) would return a different value.
+    ///
+    /// Subscriptions aren't cloned or serialized, and are ignored when comparing `Accounts`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use hashmap_settings::account::{Account,Valid};
+    ///
+    /// let mut account = Account::<String,&str,i32>::new(
+    ///     "Main".to_string(),
+    ///     true,
+    ///     Default::default(),
+    ///     vec![Account::new("Default".to_string(), true, [("lines", 3)].into(), vec![])],
+    /// );
+    /// let changes = Arc::new(Mutex::new(vec![]));
+    /// let changes_clone = Arc::clone(&changes);
+    /// account.subscribe("lines", move |_, old, new| {
+    ///     changes_clone.lock().unwrap().push((old.copied(), new.copied()));
+    /// });
+    ///
+    /// account.deep_insert(&"lines", 5, &mut vec![&"Default".to_string()])?;
+    /// account.deep_insert(&"lines", 5, &mut vec![&"Default".to_string()])?;
+    /// account.push(Account::new("Local".to_string(), true, [("lines", 7)].into(), vec![]), Valid::new_true());
+    /// account.pop(Valid::new_true());
+    /// account.insert("words", 2);
+    ///
+    /// assert_eq!(
+    ///     *changes.lock().unwrap(),
+    ///     vec![(Some(3), Some(5)), (Some(5), Some(7)), (Some(7), Some(5))]
+    /// );
+    /// # Ok::<(), hashmap_settings::account::DeepError>(())
+    /// ```
+    pub fn subscribe<F: FnMut(&K, Option<&V>, Option<&V>) + Send + Sync + 'static>(
+        &mut self,
+        setting_name: K,
+        callback: F,
+    ) -> SubscriptionId {
+        let mut callback = callback;
+        self.add_subscriber(
+            Some(setting_name),
+            Box::new(move |key, old, new| {
+                callback(key, old, new);
+                true
+            }),
+            None,
+        )
+    }
+    /// Registers a callback that is called when the value of any setting changes
+    ///
+    /// Works like [`subscribe`](Account::subscribe) but for all settings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use hashmap_settings::account::Account;
+    ///
+    /// let mut account = Account::<(),&str,i32>::default();
+    /// let changed = Arc::new(Mutex::new(vec![]));
+    /// let changed_clone = Arc::clone(&changed);
+    /// account.subscribe_all(move |key, _, _| changed_clone.lock().unwrap().push(*key));
+    ///
+    /// account.insert("lines", 3);
+    /// account.insert("words", 2);
+    /// account.remove(&"lines");
+    ///
+    /// assert_eq!(*changed.lock().unwrap(), vec!["lines", "words", "lines"]);
+    /// ```
+    pub fn subscribe_all<F: FnMut(&K, Option<&V>, Option<&V>) + Send + Sync + 'static>(
+        &mut self,
+        callback: F,
+    ) -> SubscriptionId {
+        let mut callback = callback;
+        self.add_subscriber(
+            None,
+            Box::new(move |key, old, new| {
+                callback(key, old, new);
+                true
+            }),
+            None,
+        )
+    }
+    /// Adds a subscriber, removing the ones that are no longer [open](Open).
+    pub(super) fn add_subscriber(
+        &mut self,
+        key: Option<K>,
+        callback: Callback<K, V>,
+        open: Option<Open>,
+    ) -> SubscriptionId {
+        let subscriptions = &mut self.subscriptions;
+        subscriptions.subscribers.retain(Subscriber::is_open);
+        let id = SubscriptionId(subscriptions.next_id);
+        subscriptions.next_id += 1;
+        subscriptions.eq = Some(<V as PartialEq>::eq);
+        subscriptions.subscribers.push(Subscriber {
+            id,
+            key,
+            callback,
+            open,
+        });
+        id
+    }
+}
+impl<N, K, V> Account<N, K, V> {
+    /// Removes a subscription, returning `true` if it existed
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::Account;
+    ///
+    /// let mut account = Account::<(),&str,i32>::default();
+    /// let id = account.subscribe_all(|_, _, _| panic!("unsubscribed"));
+    /// assert!(account.unsubscribe(id));
+    /// assert!(!account.unsubscribe(id));
+    /// account.insert("lines", 3);
+    /// ```
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        let subscribers = &mut self.subscriptions.subscribers;
+        let len = subscribers.len();
+        subscribers.retain(|subscriber| subscriber.id != id);
+        subscribers.len() != len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    };
+
+    use crate::account::{Account, Valid};
+
+    type Changes = Arc<Mutex<Vec<(&'static str, Option<i32>, Option<i32>)>>>;
+
+    fn record(account: &mut Account<String, &'static str, i32>) -> Changes {
+        let changes = Changes::default();
+        let changes_clone = Arc::clone(&changes);
+        account.subscribe_all(move |key, old, new| {
+            changes_clone
+                .lock()
+                .unwrap()
+                .push((key, old.copied(), new.copied()));
+        });
+        changes
+    }
+    fn take(changes: &Changes) -> Vec<(&'static str, Option<i32>, Option<i32>)> {
+        let mut changes = core::mem::take(&mut *changes.lock().unwrap());
+        changes.sort_unstable();
+        changes
+    }
+
+    #[test]
+    fn deep_changes() {
+        let mut account = Account::new(
+            "Main".to_string(),
+            true,
+            HashMap::new(),
+            vec![
+                Account::new("Default".to_string(), true, [("lines", 3)].into(), vec![]),
+                Account::new(
+                    "Local".to_string(),
+                    true,
+                    HashMap::new(),
+                    vec![Account::new(
+                        "Project".to_string(),
+                        true,
+                        [("words", 2)].into(),
+                        vec![],
+                    )],
+                ),
+            ],
+        );
+        let changes = record(&mut account);
+        let (project, local) = ("Project".to_string(), "Local".to_string());
+        let path = || vec![&project, &local];
+
+        account.deep_insert(&"lines", 4, &mut path()).unwrap();
+        assert_eq!(take(&changes), vec![("lines", Some(3), Some(4))]);
+        account.deep_change_activity(false, &mut path()).unwrap();
+        assert_eq!(
+            take(&changes),
+            vec![("lines", Some(4), Some(3)), ("words", Some(2), None)]
+        );
+        account.deep_change_activity(true, &mut path()).unwrap();
+        assert_eq!(
+            take(&changes),
+            vec![("lines", Some(3), Some(4)), ("words", None, Some(2))]
+        );
+        account.deep_remove(&"lines", &mut path()).unwrap();
+        assert_eq!(take(&changes), vec![("lines", Some(4), Some(3))]);
+        account
+            .deep_change_activity(false, &mut vec![&"Default".to_string()])
+            .unwrap();
+        assert_eq!(take(&changes), vec![("lines", Some(3), None)]);
+    }
+    #[test]
+    fn push_and_pop() {
+        let mut account = Account::<String, &str, i32>::default();
+        account.insert("lines", 3);
+        let changes = record(&mut account);
+        account.push(
+            Account::new("Same".to_string(), true, [("lines", 3)].into(), vec![]),
+            Valid::new_true(),
+        );
+        account.push(
+            Account::new("Inactive".to_string(), false, [("lines", 5)].into(), vec![]),
+            Valid::new_true(),
+        );
+        account.pop(Valid::new_true());
+        account.pop(Valid::new_true());
+        assert_eq!(*changes.lock().unwrap(), vec![("lines", Some(3), None)]);
+    }
+    #[test]
+    fn clone_and_eq() {
+        let mut account = Account::<String, &str, i32>::default();
+        let changes = record(&mut account);
+        let mut clone = account.clone();
+        assert!(clone == account);
+        clone.insert("lines", 3);
+        assert!(changes.lock().unwrap().is_empty());
+    }
+}
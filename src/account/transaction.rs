@@ -0,0 +1,358 @@
+use std::hash::Hash;
+
+use crate::account::{Account, DeepError, DeepNames, deep_path};
+
+/// A batch of changes to the child `Accounts` of an [`Account`], applied atomically
+///
+/// Created by [`Account::transaction`]. The changes are made to the child `Accounts` right away
+/// but their parent `Accounts` are only updated when the transaction is committed, updating
+/// each affected setting once, instead of once per change.
+///
+/// Until then the settings of the parent `Accounts` of the changed `Accounts` aren't
+/// [valid](Account#valid), so [`account`](Transaction::account) should only be used to read
+/// the changed `Accounts` themselves.
+pub struct Transaction<'a, N, K, V> {
+    account: &'a mut Account<N, K, V>,
+    journal: Vec<Edit<N, K, V>>,
+    affected: Vec<Affected<N, K>>,
+}
+
+/// A change made to a child `Account`, with the value it replaced.
+enum Edit<N, K, V> {
+    Setting(Vec<N>, K, Option<V>),
+    Activity(Vec<N>, bool),
+    /// A tombstone removed from the `Account`.
+    Tombstone(Vec<N>, K),
+}
+/// A setting of an `Account` affected by the transaction.
+///
+/// `update` is `false` if the last change to the setting was made directly to the `Account`,
+/// and `true` if it was made to one of its child `Accounts`, so the setting needs to be updated.
+struct Affected<N, K> {
+    path: Vec<N>,
+    key: K,
+    update: bool,
+}
+
+impl<N: Clone + PartialEq, K: Clone + Eq + Hash, V: Clone> Account<N, K, V> {
+    /// Makes multiple changes to child `Accounts` atomically
+    ///
+    /// The closure receives a [`Transaction`] to make the changes. If it returns `Ok` the changes are
+    /// committed, updating the settings of the parent `Accounts` once per affected setting.
+    /// If it returns `Err` all changes are undone and the `Account` is left as it was before.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error returned by the closure.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::{Account,DeepError};
+    ///
+    /// let mut account = Account::<String,&str,i32>::new(
+    ///     "Main".to_string(),
+    ///     true,
+    ///     Default::default(),
+    ///     vec![Account::new("Default".to_string(), true, [("lines", 3)].into(), vec![])],
+    /// );
+    /// let before = account.clone();
+    /// let default = "Default".to_string();
+    ///
+    /// let result = account.transaction(|tx| {
+    ///     tx.deep_insert(&"lines", 5, &mut vec![&default])?;
+    ///     tx.deep_insert(&"words", 2, &mut vec![&"Missing".to_string()])?;
+    ///     Ok(())
+    /// });
+    /// assert_eq!(result, Err(DeepError::NotFound));
+    /// assert_eq!(account, before);
+    ///
+    /// account.transaction(|tx| {
+    ///     tx.deep_insert(&"lines", 5, &mut vec![&default])?;
+    ///     tx.deep_insert(&"words", 2, &mut vec![&default])?;
+    ///     Ok::<(), DeepError>(())
+    /// })?;
+    /// assert_eq!(account.get(&"lines"), Some(&5));
+    /// assert_eq!(account.get(&"words"), Some(&2));
+    /// # Ok::<(), DeepError>(())
+    /// ```
+    pub fn transaction<T, E, F: FnOnce(&mut Transaction<'_, N, K, V>) -> Result<T, E>>(
+        &mut self,
+        f: F,
+    ) -> Result<T, E> {
+        let mut transaction = Transaction {
+            account: self,
+            journal: vec![],
+            affected: vec![],
+        };
+        let result = f(&mut transaction);
+        if result.is_ok() {
+            transaction.commit();
+        } else {
+            transaction.rollback();
+        }
+        result
+    }
+}
+
+impl<N, K, V> Transaction<'_, N, K, V> {
+    /// Returns a reference to the `Account`
+    ///
+    /// The settings of the parent `Accounts` of the changed `Accounts` are only updated when the transaction is committed.
+    pub const fn account(&self) -> &Account<N, K, V> {
+        self.account
+    }
+}
+impl<N: Clone + PartialEq, K: Clone + Eq + Hash, V: Clone> Transaction<'_, N, K, V> {
+    /// Inserts a key-value pair into the map of a child `Account`
+    ///
+    /// Transaction version of [`Account::deep_insert`].
+    ///
+    /// # Errors
+    ///
+    /// Deep functions can return [`DeepError`]'s
+    pub fn deep_insert<'a>(
+        &mut self,
+        setting_name: &K,
+        setting_value: V,
+        account_names: impl DeepNames<'a, N>,
+    ) -> Result<Option<V>, DeepError>
+    where
+        N: 'a,
+    {
+        let path = deep_path(account_names.deep_names())?;
+        if self.account.locked_in_path(&path, setting_name) {
+            return Err(DeepError::Locked);
+        }
+        let account = self.account.walk_mut(&path).ok_or(DeepError::NotFound)?;
+        let old = account.subscriptions.insert(
+            &mut account.settings,
+            setting_name.clone(),
+            setting_value,
+        );
+        if account.remove_tombstone(setting_name) {
+            self.journal
+                .push(Edit::Tombstone(path.clone(), setting_name.clone()));
+        }
+        self.affect(&path, setting_name);
+        self.journal
+            .push(Edit::Setting(path, setting_name.clone(), old.clone()));
+        Ok(old)
+    }
+    /// Removes a setting from the map of a child `Account`
+    ///
+    /// Transaction version of [`Account::deep_remove`].
+    ///
+    /// # Errors
+    ///
+    /// Deep functions can return [`DeepError`]'s
+    pub fn deep_remove<'a>(
+        &mut self,
+        setting_to_remove: &K,
+        account_names: impl DeepNames<'a, N>,
+    ) -> Result<Option<V>, DeepError>
+    where
+        N: 'a,
+    {
+        let path = deep_path(account_names.deep_names())?;
+        let account = self.account.walk_mut(&path).ok_or(DeepError::NotFound)?;
+        let old = account
+            .subscriptions
+            .remove(&mut account.settings, setting_to_remove);
+        if account.remove_tombstone(setting_to_remove) {
+            self.journal
+                .push(Edit::Tombstone(path.clone(), setting_to_remove.clone()));
+        }
+        self.affect(&path, setting_to_remove);
+        self.journal
+            .push(Edit::Setting(path, setting_to_remove.clone(), old.clone()));
+        Ok(old)
+    }
+    /// Changes the activity of a child `Account`, returning `true` if it changed
+    ///
+    /// Transaction version of [`Account::deep_change_activity`].
+    ///
+    /// # Errors
+    ///
+    /// Deep functions can return [`DeepError`]'s
+    pub fn deep_change_activity<'a>(
+        &mut self,
+        new_active: bool,
+        account_names: impl DeepNames<'a, N>,
+    ) -> Result<bool, DeepError>
+    where
+        N: 'a,
+    {
+        let path = deep_path(account_names.deep_names())?;
+        let account = self.account.walk_mut(&path).ok_or(DeepError::NotFound)?;
+        let old = account.active;
+        if old == new_active {
+            return Ok(false);
+        }
+        account.active = new_active;
+        let keys = account
+            .layer_keys()
+            .into_iter()
+            .cloned()
+            .collect::<Vec<_>>();
+        self.account.clear_locks_in_path(&path);
+        for key in &keys {
+            // the settings of the `Account` itself don't change, only those of its parents
+            self.update_parents(&path, key);
+        }
+        self.journal.push(Edit::Activity(path, old));
+        Ok(true)
+    }
+
+    /// Marks a setting as changed directly in the `Account` at `path`, and to be updated in its parents.
+    fn affect(&mut self, path: &[N], key: &K) {
+        self.set_update(path, key, false);
+        self.update_parents(path, key);
+    }
+    fn update_parents(&mut self, path: &[N], key: &K) {
+        for parent in 1..=path.len() {
+            self.set_update(&path[parent..], key, true);
+        }
+    }
+    fn set_update(&mut self, path: &[N], key: &K, update: bool) {
+        if let Some(affected) = self
+            .affected
+            .iter_mut()
+            .find(|affected| affected.path == path && &affected.key == key)
+        {
+            affected.update = update;
+        } else {
+            self.affected.push(Affected {
+                path: path.to_vec(),
+                key: key.clone(),
+                update,
+            });
+        }
+    }
+    fn commit(mut self) {
+        // child `Accounts` are updated before their parents
+        self.affected
+            .sort_by_key(|affected| core::cmp::Reverse(affected.path.len()));
+        for affected in &self.affected {
+            if affected.update
+                && let Some(account) = self.account.walk_mut(&affected.path)
+            {
+                account.update_setting(&affected.key);
+            }
+        }
+    }
+    fn rollback(self) {
+        for edit in self.journal.into_iter().rev() {
+            match edit {
+                Edit::Setting(path, key, old) => {
+                    if let Some(account) = self.account.walk_mut(&path) {
+                        match old {
+                            Some(old) => {
+                                account
+                                    .subscriptions
+                                    .insert(&mut account.settings, key, old)
+                            }
+                            None => account.subscriptions.remove(&mut account.settings, &key),
+                        };
+                    }
+                }
+                Edit::Activity(path, old) => {
+                    if let Some(account) = self.account.walk_mut(&path) {
+                        account.active = old;
+                    }
+                    self.account.clear_locks_in_path(&path);
+                }
+                Edit::Tombstone(path, key) => {
+                    if let Some(account) = self.account.walk_mut(&path) {
+                        account.add_tombstone(key);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::account::{Account, DeepError};
+
+    fn account() -> Account<String, &'static str, i32> {
+        Account::new(
+            "Main".to_string(),
+            true,
+            HashMap::new(),
+            vec![
+                Account::new("Default".to_string(), true, [("lines", 3)].into(), vec![]),
+                Account::new(
+                    "Local".to_string(),
+                    true,
+                    HashMap::new(),
+                    vec![Account::new(
+                        "Project".to_string(),
+                        true,
+                        [("words", 2)].into(),
+                        vec![],
+                    )],
+                ),
+            ],
+        )
+    }
+
+    #[test]
+    fn same_as_deep_functions() {
+        let (default, local, project) = (
+            "Default".to_string(),
+            "Local".to_string(),
+            "Project".to_string(),
+        );
+        let mut expected = account();
+        expected
+            .deep_insert(&"lines", 5, &mut vec![&project, &local])
+            .unwrap();
+        expected
+            .deep_insert(&"words", 4, &mut vec![&local])
+            .unwrap();
+        expected.deep_remove(&"lines", &mut vec![&default]).unwrap();
+        expected
+            .deep_change_activity(false, &mut vec![&project, &local])
+            .unwrap();
+        expected
+            .deep_insert(&"ratio", 1, &mut vec![&project, &local])
+            .unwrap();
+
+        let mut account = account();
+        let result = account.transaction(|tx| {
+            assert_eq!(
+                tx.deep_insert(&"lines", 5, &mut vec![&project, &local]),
+                Ok(None)
+            );
+            assert_eq!(tx.deep_insert(&"words", 4, &mut vec![&local]), Ok(Some(2)));
+            assert_eq!(tx.deep_remove(&"lines", &mut vec![&default]), Ok(Some(3)));
+            assert_eq!(
+                tx.deep_change_activity(false, &mut vec![&project, &local]),
+                Ok(true)
+            );
+            tx.deep_insert(&"ratio", 1, &mut vec![&project, &local])
+        });
+        assert_eq!(result, Ok(None));
+        assert_eq!(account, expected);
+        assert_eq!(account.get(&"words"), None);
+        assert_eq!(account.get(&"ratio"), None);
+        assert_eq!(account.get(&"lines"), None);
+    }
+    #[test]
+    fn rollback() {
+        let local = "Local".to_string();
+        let mut account = account();
+        let result = account.transaction(|tx| {
+            tx.deep_insert(&"words", 4, &mut vec![&local])?;
+            tx.deep_change_activity(false, &mut vec![&local])?;
+            tx.deep_remove(&"lines", &mut vec![&"Default".to_string()])?;
+            tx.deep_remove(&"lines", &mut vec![])
+        });
+        assert_eq!(result, Err(DeepError::EmptyVec));
+        assert_eq!(account, self::account());
+    }
+}
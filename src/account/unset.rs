@@ -0,0 +1,398 @@
+use std::hash::Hash;
+
+use crate::account::{Account, DeepError, DeepNames};
+
+impl<N, K: Eq + Hash, V> Account<N, K, V> {
+    /// Removes a setting and leaves a tombstone in its place, returning the value at the key
+    /// if the key was previously in the map.
+    ///
+    /// A tombstone marks the setting as deliberately absent: when the `Account` is the highest active child
+    /// `Account` with the setting, the search stops there and the parent `Account` won't contain the setting,
+    /// instead of taking it from a lower child `Account`.
+    ///
+    /// The tombstone is removed by [`insert`](Account::insert) and [`remove`](Account::remove).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::{Account,Valid};
+    ///
+    /// let mut local = Account::<String,&str,i32>::new("Local".to_string(), true, [("lines", 5)].into(), vec![]);
+    /// assert_eq!(local.unset("lines"), Some(5));
+    /// assert_eq!(local.get(&"lines"), None);
+    ///
+    /// let mut account = Account::new(
+    ///     "Main".to_string(),
+    ///     true,
+    ///     Default::default(),
+    ///     vec![Account::new("Default".to_string(), true, [("lines", 3)].into(), vec![])],
+    /// );
+    /// account.push(local, Valid::new_true());
+    /// assert_eq!(account.get(&"lines"), None);
+    /// ```
+    pub fn unset(&mut self, setting_name: K) -> Option<V> {
+        let old = self.subscriptions.remove(&mut self.settings, &setting_name);
+        self.add_tombstone(setting_name);
+        old
+    }
+    /// Returns the settings unset by a tombstone in the `Account` itself, in the order they were unset
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::Account;
+    ///
+    /// let mut account = Account::<(),&str,i32>::default();
+    /// account.unset("lines");
+    /// account.unset("words");
+    /// assert_eq!(account.unset_settings(), &["lines", "words"]);
+    /// ```
+    #[must_use]
+    pub fn unset_settings(&self) -> &[K] {
+        &self.unset
+    }
+    /// Adds a tombstone for a setting, without removing its value, returning `true` if there wasn't one.
+    pub(super) fn add_tombstone(&mut self, setting: K) -> bool {
+        if self.unset.contains(&setting) {
+            return false;
+        }
+        self.unset.push(setting);
+        self.snapshot.clear();
+        true
+    }
+    /// Removes the tombstone of a setting, returning `true` if there was one.
+    pub(super) fn remove_tombstone(&mut self, setting: &K) -> bool {
+        let Some(index) = self.unset.iter().position(|unset| unset == setting) else {
+            return false;
+        };
+        self.unset.remove(index);
+        self.snapshot.clear();
+        true
+    }
+    /// Returns the settings of the `Account` and the settings unset or locked in it or in its child `Accounts`,
+    /// which are the settings that need to be updated in a parent `Account` when this one changes.
+    pub(super) fn layer_keys(&self) -> Vec<&K> {
+        let mut keys = self.settings.keys().collect::<Vec<_>>();
+        self.marked_keys(&mut keys);
+        keys
+    }
+    fn marked_keys<'a>(&'a self, keys: &mut Vec<&'a K>) {
+        keys.extend(&self.unset);
+        keys.extend(&self.locked);
+        for account in &self.accounts {
+            account.marked_keys(keys);
+        }
+    }
+}
+impl<N, K: Clone + Eq + Hash, V> Account<N, K, V> {
+    /// Returns `true` if the setting is unset, by a tombstone left with [`unset`](Account::unset)
+    /// in the `Account` or in the child `Account` the setting would be taken from.
+    ///
+    /// Use [`unset_by`](Account::unset_by) to know which `Account` holds the tombstone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::Account;
+    ///
+    /// let mut account = Account::<(),&str,i32>::default();
+    /// account.insert("lines", 3);
+    /// account.unset("lines");
+    /// assert!(account.is_unset(&"lines"));
+    /// assert!(!account.is_unset(&"words"));
+    ///
+    /// account.insert("lines", 5);
+    /// assert!(!account.is_unset(&"lines"));
+    /// ```
+    #[must_use]
+    pub fn is_unset(&self, setting_name: &K) -> bool {
+        if self.settings.contains_key(setting_name) {
+            return false;
+        }
+        for account in self.layers(setting_name).rev() {
+            if account.settings.contains_key(setting_name) {
+                return false;
+            }
+            if account.is_unset(setting_name) {
+                return true;
+            }
+        }
+        self.unset.contains(setting_name)
+    }
+    /// Returns `true` if the value of a setting in the `Accounts` below this one is hidden by a tombstone,
+    /// in the `Account` or in the child `Accounts` its value is taken from.
+    pub(super) fn masks(&self, setting: &K) -> bool {
+        if self.unset.contains(setting) {
+            return true;
+        }
+        let merged = self.merge.contains(setting);
+        for account in self.layers(setting).rev() {
+            if account.masks(setting) {
+                return true;
+            }
+            if !merged && account.settings.contains_key(setting) {
+                return false;
+            }
+        }
+        false
+    }
+}
+impl<N: Clone, K: Clone + Eq + Hash, V> Account<N, K, V> {
+    /// Returns the names of the `Accounts`, from the direct child to the `Account` that holds the tombstone,
+    /// if the setting is [unset](Account::is_unset).
+    ///
+    /// The names are empty if the tombstone is in the `Account` itself. Returns `None` for a setting
+    /// that has a value or was never defined.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::Account;
+    ///
+    /// let mut account = Account::<String,&str,i32>::new(
+    ///     "Main".to_string(),
+    ///     true,
+    ///     Default::default(),
+    ///     vec![
+    ///         Account::new("Default".to_string(), true, [("lines", 3)].into(), vec![]),
+    ///         Account::new("Local".to_string(), true, Default::default(), vec![]),
+    ///     ],
+    /// );
+    /// account.deep_unset(&"lines", &mut vec![&"Local".to_string()])?;
+    ///
+    /// assert_eq!(account.get(&"lines"), None);
+    /// assert_eq!(account.unset_by(&"lines"), Some(vec!["Local".to_string()]));
+    /// assert_eq!(account.unset_by(&"words"), None);
+    /// # Ok::<(), hashmap_settings::account::DeepError>(())
+    /// ```
+    #[must_use]
+    pub fn unset_by(&self, setting_name: &K) -> Option<Vec<N>> {
+        if self.settings.contains_key(setting_name) {
+            return None;
+        }
+        for account in self.layers(setting_name).rev() {
+            if account.settings.contains_key(setting_name) {
+                return None;
+            }
+            if let Some(mut path) = account.unset_by(setting_name) {
+                path.insert(0, account.name.clone());
+                return Some(path);
+            }
+        }
+        self.unset.contains(setting_name).then(Vec::new)
+    }
+}
+impl<N: PartialEq, K: Clone + Eq + Hash, V: Clone> Account<N, K, V> {
+    /// Removes a setting from a child `Account` and leaves a tombstone in its place, returning the value at the key
+    /// if the key was previously in the map.
+    ///
+    /// Part of the [deep functions](Account#deep-functions) group that accept a `Vec` of &N to identify
+    /// the child `Account` to run the function. [`unset`](Account::unset) in this case.
+    ///
+    /// Also updates the setting in all the affected accounts such that they contain the correct values.
+    ///
+    /// # Errors
+    ///
+    /// Deep functions can return [`DeepError`]'s
+    ///
+    /// Returns [`DeepError::Locked`] if the setting is [locked](Account::lock) below the child `Account`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::account::Account;
+    ///
+    /// let mut account = Account::<String,&str,i32>::new(
+    ///     "Main".to_string(),
+    ///     true,
+    ///     Default::default(),
+    ///     vec![
+    ///         Account::new("Default".to_string(), true, [("lines", 3)].into(), vec![]),
+    ///         Account::new("Local".to_string(), true, [("lines", 5)].into(), vec![]),
+    ///     ],
+    /// );
+    /// assert_eq!(account.deep_unset(&"lines", &mut vec![&"Local".to_string()]), Ok(Some(5)));
+    /// assert_eq!(account.get(&"lines"), None);
+    ///
+    /// account.deep_remove(&"lines", &mut vec![&"Local".to_string()])?;
+    /// assert_eq!(account.get(&"lines"), Some(&3));
+    /// # Ok::<(), hashmap_settings::account::DeepError>(())
+    /// ```
+    pub fn deep_unset<'a>(
+        &mut self,
+        setting_name: &K,
+        account_names: impl DeepNames<'a, N>,
+    ) -> Result<Option<V>, DeepError>
+    where
+        N: 'a,
+    {
+        let account_names = &mut account_names.deep_names();
+        let Some(account_to_find) = account_names.pop() else {
+            return Err(DeepError::EmptyVec);
+        };
+        if self.locked_below(account_to_find, setting_name) {
+            return Err(DeepError::Locked);
+        }
+        let Some(found_account) = self.mut_account_from_name(account_to_find) else {
+            return Err(DeepError::NotFound);
+        };
+        let old = if account_names.is_empty() {
+            found_account.unset(setting_name.clone())
+        } else {
+            found_account.deep_unset(setting_name, account_names)?
+        };
+        self.update_setting(setting_name);
+        Ok(old)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::account::{Account, DeepError, History, MergePolicy, Valid};
+
+    fn account() -> Account<String, &'static str, Vec<i32>> {
+        Account::new(
+            "Main".to_string(),
+            true,
+            HashMap::new(),
+            vec![
+                Account::new(
+                    "Default".to_string(),
+                    true,
+                    [("lines", vec![3]), ("words", vec![2])].into(),
+                    vec![],
+                ),
+                Account::new(
+                    "Project".to_string(),
+                    true,
+                    HashMap::new(),
+                    vec![
+                        Account::new("Shared".to_string(), true, HashMap::new(), vec![]),
+                        Account::new("Local".to_string(), true, HashMap::new(), vec![]),
+                    ],
+                ),
+            ],
+        )
+    }
+
+    #[test]
+    fn tombstones_mask_lower_layers() {
+        let mut account = account();
+        let (project, shared, local) = (
+            "Project".to_string(),
+            "Shared".to_string(),
+            "Local".to_string(),
+        );
+
+        account
+            .deep_unset(&"lines", &mut vec![&shared, &project])
+            .unwrap();
+        assert_eq!(account.get(&"lines"), None);
+        assert_eq!(
+            account.unset_by(&"lines"),
+            Some(vec![project.clone(), shared.clone()])
+        );
+        assert_eq!(account.unset_by(&"words"), None);
+        assert_eq!(account.unset_by(&"width"), None);
+        assert_eq!(account.snapshot().to_account(), account);
+
+        account
+            .deep_insert(&"lines", vec![5], &mut vec![&local, &project])
+            .unwrap();
+        assert_eq!(account.get(&"lines"), Some(&vec![5]));
+        assert_eq!(account.unset_by(&"lines"), None);
+
+        account
+            .deep_change_activity(false, &mut vec![&local, &project])
+            .unwrap();
+        assert!(account.is_unset(&"lines"));
+        account
+            .deep_change_activity(false, &mut vec![&shared, &project])
+            .unwrap();
+        assert_eq!(account.get(&"lines"), Some(&vec![3]));
+
+        account
+            .deep_change_activity(true, &mut vec![&shared, &project])
+            .unwrap();
+        assert_eq!(account.get(&"lines"), None);
+        let mut pop = || {
+            account
+                .deep_pop(Valid::new_true(), &mut vec![&project])
+                .unwrap()
+                .unwrap()
+        };
+        let (local_account, shared_account) = (pop(), pop());
+        assert_eq!(account.get(&"lines"), Some(&vec![3]));
+        for child in [shared_account, local_account] {
+            assert_eq!(
+                account.deep_push(child, Valid::new_true(), &mut vec![&project]),
+                None
+            );
+        }
+        assert_eq!(account.get(&"lines"), None);
+
+        account
+            .deep_remove(&"lines", &mut vec![&shared, &project])
+            .unwrap();
+        assert_eq!(account.get(&"lines"), Some(&vec![3]));
+        assert!(account.valid().is_valid());
+    }
+    #[test]
+    fn tombstones_in_merged_settings() {
+        let mut account = account();
+        let (project, shared, local) = (
+            "Project".to_string(),
+            "Shared".to_string(),
+            "Local".to_string(),
+        );
+        account.set_merge_policy(&"lines", MergePolicy::Append);
+        account
+            .deep_insert(&"lines", vec![4], &mut vec![&shared, &project])
+            .unwrap();
+        account
+            .deep_insert(&"lines", vec![5], &mut vec![&local, &project])
+            .unwrap();
+        assert_eq!(account.get(&"lines"), Some(&vec![3, 4, 5]));
+
+        account
+            .deep_unset(&"lines", &mut vec![&shared, &project])
+            .unwrap();
+        assert_eq!(account.get(&"lines"), Some(&vec![5]));
+
+        let snapshot = account.snapshot();
+        assert_eq!(snapshot.get(&"lines"), Some(&vec![5]));
+        assert_eq!(snapshot.to_account(), account);
+
+        let mut updated = account.clone();
+        updated.update_valid(Valid::new_false());
+        assert!(updated.valid().is_valid());
+    }
+    #[test]
+    fn tombstones_are_restored() {
+        let mut account = account();
+        let (project, shared) = ("Project".to_string(), "Shared".to_string());
+        account
+            .deep_unset(&"lines", &mut vec![&shared, &project])
+            .unwrap();
+        let before = account.clone();
+
+        let result = account.transaction(|tx| {
+            tx.deep_insert(&"lines", vec![4], &mut vec![&shared, &project])?;
+            Err::<(), _>(DeepError::NotFound)
+        });
+        assert!(result.is_err());
+        assert_eq!(account, before);
+        assert!(account.is_unset(&"lines"));
+
+        let mut history = History::new(account);
+        history
+            .deep_remove(&"lines", &mut vec![&shared, &project])
+            .unwrap();
+        assert_eq!(history.account().get(&"lines"), Some(&vec![3]));
+        assert!(history.undo());
+        assert_eq!(history.account(), &before);
+        assert_eq!(history.account().get(&"lines"), None);
+    }
+}
@@ -0,0 +1,290 @@
+use core::{
+    future::poll_fn,
+    task::{Context, Poll, Waker},
+};
+use std::{
+    hash::Hash,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use crate::{
+    account::{Account, SubscriptionId},
+    stg::{Setting, Stg, StgError, StgTrait},
+};
+
+/// Receiver of the changes of a setting of an [`Account`]
+///
+/// Created by [`Account::watch`], it yields the new value of the setting, already converted
+/// to `T`, whenever it changes. If the setting changes multiple times before the value is received
+/// only the last value is kept.
+///
+/// Dropping the `Watch` removes its subscription the next time any setting of the `Account` changes,
+/// or a new subscription is added, without waiting for the watched setting to change.
+///
+/// It doesn't depend on any async runtime, so [`changed`](Watch::changed) can be awaited on any of them,
+/// and used with macros like `select!`.
+///
+/// Requires the "async" feature.
+pub struct Watch<T> {
+    id: SubscriptionId,
+    state: Arc<Mutex<State<T>>>,
+}
+struct State<T> {
+    value: Option<Result<T, StgError>>,
+    waker: Option<Waker>,
+    /// Set when either the `Watch` or the `Sender` is dropped.
+    closed: bool,
+}
+/// The sending side, owned by the callback of the subscription, that closes the `Watch` when dropped.
+struct Sender<T> {
+    state: Arc<Mutex<State<T>>>,
+}
+impl<T> Sender<T> {
+    /// Sends the value to the `Watch`, returning `false` if it was dropped.
+    fn send(&self, value: impl FnOnce() -> Result<T, StgError>) -> bool {
+        let waker = {
+            let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+            if state.closed {
+                return false;
+            }
+            state.value = Some(value());
+            state.waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+        true
+    }
+}
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.closed = true;
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> Watch<T> {
+    /// Waits for the setting to change, returning its new value
+    ///
+    /// The value is `Err(StgError::None)` if the setting was removed and `Err(StgError::WrongType)`
+    /// if it isn't of type `T`.
+    ///
+    /// Returns `None` once the subscription is gone, because the `Account` was dropped or
+    /// [`unsubscribe`](Account::unsubscribe) was called with the [`id`](Watch::id) of the `Watch`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::{account::Account,stg::{Setting,Stg}};
+    /// # fn block_on<F: Future>(future: F) -> F::Output {
+    /// #     let mut future = std::pin::pin!(future);
+    /// #     let mut context = std::task::Context::from_waker(std::task::Waker::noop());
+    /// #     loop {
+    /// #         if let std::task::Poll::Ready(output) = future.as_mut().poll(&mut context) {
+    /// #             return output;
+    /// #         }
+    /// #     }
+    /// # }
+    ///
+    /// let mut account = Account::<(),&str,Stg>::default();
+    /// let mut watch = account.watch::<i32>(&"lines");
+    ///
+    /// account.insert("lines", 3.stg());
+    /// account.insert("lines", 5.stg());
+    /// assert_eq!(block_on(watch.changed()), Some(Ok(5)));
+    ///
+    /// drop(account);
+    /// assert_eq!(block_on(watch.changed()), None);
+    /// ```
+    pub async fn changed(&mut self) -> Option<Result<T, StgError>> {
+        poll_fn(|context| self.poll_changed(context)).await
+    }
+    fn poll_changed(&self, context: &Context<'_>) -> Poll<Option<Result<T, StgError>>> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(value) = state.value.take() {
+            return Poll::Ready(Some(value));
+        }
+        if state.closed {
+            return Poll::Ready(None);
+        }
+        state.waker = Some(context.waker().clone());
+        Poll::Pending
+    }
+    /// Returns the [`SubscriptionId`] of the subscription used by the `Watch`
+    #[must_use]
+    pub const fn id(&self) -> SubscriptionId {
+        self.id
+    }
+}
+impl<T> Drop for Watch<T> {
+    fn drop(&mut self) {
+        // the subscription is removed the next time the `Account` notifies or adds a subscriber
+        self.state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .closed = true;
+    }
+}
+
+impl<N, K: Clone + Eq + Hash> Account<N, K, Stg> {
+    /// Returns a [`Watch`] that receives the value of a setting whenever it changes
+    ///
+    /// The value is converted with [`unstg`](StgTrait::unstg). Works like [`subscribe`](Account::subscribe),
+    /// so it receives every change to the value returned by [`get`](Account::get).
+    ///
+    /// `T` has to be `Send` so the `Watch` can be moved to the thread that awaits it.
+    ///
+    /// Requires the "async" feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use hashmap_settings::{account::Account,stg::{Setting,Stg,StgError}};
+    /// # fn block_on<F: Future>(future: F) -> F::Output {
+    /// #     struct Unpark(thread::Thread);
+    /// #     impl std::task::Wake for Unpark {
+    /// #         fn wake(self: std::sync::Arc<Self>) {
+    /// #             self.0.unpark();
+    /// #         }
+    /// #     }
+    /// #     let waker = std::sync::Arc::new(Unpark(thread::current())).into();
+    /// #     let mut context = std::task::Context::from_waker(&waker);
+    /// #     let mut future = std::pin::pin!(future);
+    /// #     loop {
+    /// #         match future.as_mut().poll(&mut context) {
+    /// #             std::task::Poll::Ready(output) => return output,
+    /// #             std::task::Poll::Pending => thread::park(),
+    /// #         }
+    /// #     }
+    /// # }
+    ///
+    /// let mut account = Account::<(),&str,Stg>::default();
+    /// let mut watch = account.watch::<String>(&"theme");
+    ///
+    /// let reader = thread::spawn(move || {
+    ///     let mut values = vec![];
+    ///     while let Some(value) = block_on(watch.changed()) {
+    ///         values.push(value);
+    ///     }
+    ///     values
+    /// });
+    /// account.insert("theme", "dark".to_string().stg());
+    /// account.remove(&"theme");
+    /// drop(account);
+    /// let values = reader.join().unwrap();
+    ///
+    /// // the first value could have been replaced before being received
+    /// assert_eq!(values.last(), Some(&Err(StgError::None)));
+    /// ```
+    pub fn watch<T: Setting + Send>(&mut self, setting_name: &K) -> Watch<T> {
+        let state = Arc::new(Mutex::new(State {
+            value: None,
+            waker: None,
+            closed: false,
+        }));
+        let sender = Sender {
+            state: Arc::clone(&state),
+        };
+        let open = Arc::clone(&state);
+        let id = self.add_subscriber(
+            Some(setting_name.clone()),
+            Box::new(move |_, _, new| sender.send(|| new.unstg::<T>())),
+            Some(Box::new(move || {
+                !open.lock().unwrap_or_else(PoisonError::into_inner).closed
+            })),
+        );
+        Watch { id, state }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::{
+        pin::pin,
+        task::{Context, Poll, Waker},
+    };
+    use std::collections::HashMap;
+
+    use crate::{
+        account::Account,
+        stg::{Setting, Stg, StgError},
+    };
+
+    fn poll<F: Future>(future: F) -> Poll<F::Output> {
+        pin!(future).poll(&mut Context::from_waker(Waker::noop()))
+    }
+
+    #[test]
+    fn resolved_changes() {
+        let mut account = Account::<String, &str, Stg>::new(
+            "Main".to_string(),
+            true,
+            HashMap::new(),
+            vec![
+                Account::new(
+                    "Default".to_string(),
+                    true,
+                    [("lines", 3.stg())].into(),
+                    vec![],
+                ),
+                Account::new("Local".to_string(), true, HashMap::new(), vec![]),
+            ],
+        );
+        let mut watch = account.watch::<i32>(&"lines");
+        let local = "Local".to_string();
+        assert_eq!(poll(watch.changed()), Poll::Pending);
+
+        account
+            .deep_insert(&"lines", 5.stg(), &mut vec![&local])
+            .unwrap();
+        assert_eq!(poll(watch.changed()), Poll::Ready(Some(Ok(5))));
+        assert_eq!(poll(watch.changed()), Poll::Pending);
+
+        account
+            .deep_insert(&"lines", "five".to_string().stg(), &mut vec![&local])
+            .unwrap();
+        assert_eq!(
+            poll(watch.changed()),
+            Poll::Ready(Some(Err(StgError::WrongType)))
+        );
+
+        account
+            .deep_change_activity(false, &mut vec![&local])
+            .unwrap();
+        assert_eq!(poll(watch.changed()), Poll::Ready(Some(Ok(3))));
+
+        assert!(account.unsubscribe(watch.id()));
+        assert_eq!(poll(watch.changed()), Poll::Ready(None));
+    }
+    #[test]
+    fn dropped_watches_are_unsubscribed() {
+        let mut account = Account::<(), &str, Stg>::default();
+        let watch = account.watch::<i32>(&"lines");
+        let mut other = account.watch::<i32>(&"words");
+        assert_eq!(account.subscriptions.len(), 2);
+
+        drop(watch);
+        account.insert("lines", 3.stg());
+        assert_eq!(account.subscriptions.len(), 1);
+        account.insert("words", 2.stg());
+        assert_eq!(poll(other.changed()), Poll::Ready(Some(Ok(2))));
+    }
+    #[test]
+    fn dropped_watches_are_removed_before_their_setting_changes() {
+        let mut account = Account::<(), &str, Stg>::default();
+        let watch = account.watch::<i32>(&"lines");
+        drop(watch);
+        let _other = account.watch::<i32>(&"words");
+        assert_eq!(account.subscriptions.len(), 1);
+
+        let watch = account.watch::<i32>(&"lines");
+        assert_eq!(account.subscriptions.len(), 2);
+        drop(watch);
+        account.insert("theme", "dark".to_string().stg());
+        assert_eq!(account.subscriptions.len(), 1);
+    }
+}
@@ -0,0 +1,204 @@
+//! Command-line `key=value` overrides as an `Account` layer.
+//!
+//! Each argument sets one setting, e.g. the value of `--set editor.lines=3` can be given to
+//! [`Arguments::account`] to set `editor.lines` to `3`. The `Account` created is meant to be
+//! pushed as the top layer, like the "Temporary Settings" of the [crate example](crate#example).
+//!
+//! Values are parsed into the type declared in a [`Schema`], or their type is inferred:
+//!
+//! | Value                  | `Stg`    |
+//! |------------------------|----------|
+//! | `true` or `false`      | `bool`   |
+//! | Integer                | `i64`    |
+//! | Float                  | `f64`    |
+//! | Surrounded by `"`      | `String` without the quotes |
+//! | Anything else          | `String` |
+
+use core::fmt;
+use std::collections::HashMap;
+
+use crate::{
+    account::Account,
+    layer::{ParseError, Schema},
+    stg::{Setting, Stg},
+};
+
+/// Parser of `key=value` arguments into an `Account`
+///
+/// # Examples
+///
+/// ```
+/// use hashmap_settings::{account::{Account,Valid},layer::{Schema,args::Arguments},stg::{Setting,StgTrait}};
+///
+/// let mut account = Account::<String,String,_>::default();
+/// account.insert("editor.lines".to_string(), 1_i32.stg());
+///
+/// let arguments = Arguments::new().schema(Schema::default().setting::<i32>("editor.lines".to_string()));
+/// let layer = arguments.account(
+///     "Temporary Settings".to_string(),
+///     ["editor.lines=3", "editor.wrap=true", "editor.word=temporary"],
+/// )?;
+/// account.push(layer, Valid::new_true());
+///
+/// assert_eq!(account.get(&"editor.lines".to_string()).unstg::<i32>(), Ok(3));
+/// assert_eq!(account.get(&"editor.wrap".to_string()).unstg::<bool>(), Ok(true));
+/// assert_eq!(account.get(&"editor.word".to_string()).unstg::<String>(), Ok("temporary".to_string()));
+/// # Ok::<(), hashmap_settings::layer::args::ArgsError>(())
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Arguments {
+    schema: Schema,
+}
+impl Arguments {
+    /// Creates a new `Arguments` with an empty [`Schema`], so the type of every value is inferred.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Sets the [`Schema`] with the types of the settings
+    ///
+    /// Settings that aren't in the `Schema` still have their type inferred.
+    #[must_use]
+    pub fn schema(mut self, schema: Schema) -> Self {
+        self.schema = schema;
+        self
+    }
+    /// Creates an `Account` with a setting for each `key=value` argument
+    ///
+    /// Whitespace around keys and values is ignored, and can be kept in a value by quoting it.
+    /// If a key is repeated the last value is used.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ArgsError::MissingValue`] if an argument doesn't contain `=`,
+    /// [`ArgsError::EmptyKey`] if an argument starts with `=` and
+    /// [`ArgsError::Parse`] if a value can't be parsed into the type declared in the [`Schema`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::{layer::{Schema,args::{Arguments,ArgsError}},stg::StgTrait};
+    /// let arguments = Arguments::new().schema(Schema::default().setting::<u8>("lines".to_string()));
+    ///
+    /// let layer = arguments.account("Temporary".to_string(), ["ratio=0.5", "word=\"3\""])?;
+    /// assert_eq!(layer.get(&"ratio".to_string()).unstg::<f64>(), Ok(0.5));
+    /// assert_eq!(layer.get(&"word".to_string()).unstg::<String>(), Ok("3".to_string()));
+    ///
+    /// assert_eq!(
+    ///     arguments.account("Temporary".to_string(), ["lines"]).unwrap_err(),
+    ///     ArgsError::MissingValue("lines".to_string())
+    /// );
+    /// assert_eq!(
+    ///     arguments.account("Temporary".to_string(), ["lines=many"]).unwrap_err().to_string(),
+    ///     "invalid value \"many\" for setting \"lines\", expected u8"
+    /// );
+    /// # Ok::<(), ArgsError>(())
+    /// ```
+    pub fn account<I: IntoIterator<Item = S>, S: AsRef<str>>(
+        &self,
+        name: String,
+        args: I,
+    ) -> Result<Account<String, String, Stg>, ArgsError> {
+        let mut settings = HashMap::new();
+        for arg in args {
+            let arg = arg.as_ref();
+            let Some((key, value)) = arg.split_once('=') else {
+                return Err(ArgsError::MissingValue(arg.to_string()));
+            };
+            let (key, value) = (key.trim(), value.trim());
+            if key.is_empty() {
+                return Err(ArgsError::EmptyKey(arg.to_string()));
+            }
+            let value = match self.schema.parse(key, value) {
+                Some(result) => result.map_err(ArgsError::Parse)?,
+                None => infer(value),
+            };
+            settings.insert(key.to_string(), value);
+        }
+        Ok(Account::new(name, true, settings, vec![]))
+    }
+}
+
+fn infer(value: &str) -> Stg {
+    if let Some(string) = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+    {
+        return string.to_string().stg();
+    }
+    if let Ok(bool) = value.parse::<bool>() {
+        return bool.stg();
+    }
+    if let Ok(int) = value.parse::<i64>() {
+        return int.stg();
+    }
+    if value.contains(|char: char| char.is_ascii_digit())
+        && let Ok(float) = value.parse::<f64>()
+    {
+        return float.stg();
+    }
+    value.to_string().stg()
+}
+
+/// Errors of parsing `key=value` arguments
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub enum ArgsError {
+    /// Error of an argument without `=`
+    MissingValue(String),
+    /// Error of an argument without a key before `=`
+    EmptyKey(String),
+    /// Error of a value that can't be parsed into the type declared in the [`Schema`]
+    Parse(ParseError),
+}
+impl fmt::Display for ArgsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingValue(arg) => write!(f, "expected key=value, found {arg:?}"),
+            Self::EmptyKey(arg) => write!(f, "missing key before '=' in {arg:?}"),
+            Self::Parse(error) => error.fmt(f),
+        }
+    }
+}
+impl std::error::Error for ArgsError {}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        layer::args::{ArgsError, Arguments, infer},
+        stg::{Setting, StgTrait},
+    };
+
+    #[test]
+    fn inference() {
+        assert_eq!(infer("true"), true.stg());
+        assert_eq!(infer("-3"), (-3_i64).stg());
+        assert_eq!(infer("1e3"), 1000_f64.stg());
+        assert_eq!(infer("inf"), "inf".to_string().stg());
+        assert_eq!(infer("\"true\""), "true".to_string().stg());
+        assert_eq!(infer(""), String::new().stg());
+        assert_eq!(infer("a=b"), "a=b".to_string().stg());
+    }
+    #[test]
+    fn arguments() {
+        let account = Arguments::new()
+            .account(
+                "Temporary".to_string(),
+                ["lines=3", "url=a.com/?b=c", " lines = 4", "word= \" a \" "],
+            )
+            .unwrap();
+        assert_eq!(account.get(&"lines".to_string()).unstg::<i64>(), Ok(4));
+        assert_eq!(
+            account.get(&"word".to_string()).unstg::<String>(),
+            Ok(" a ".to_string())
+        );
+        assert_eq!(
+            account.get(&"url".to_string()).unstg::<String>(),
+            Ok("a.com/?b=c".to_string())
+        );
+        assert_eq!(
+            Arguments::new().account("Temporary".to_string(), ["=3"]),
+            Err(ArgsError::EmptyKey("=3".to_string()))
+        );
+    }
+}
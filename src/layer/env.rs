@@ -0,0 +1,187 @@
+//! Environment variables as an `Account` layer.
+//!
+//! Variables starting with a prefix are turned into settings, where the rest of the
+//! variable name is split by a separator into lowercase segments joined by `.`.
+//! With the prefix `APP_` and the default separator `__` the variable `APP_EDITOR__LINES`
+//! becomes the setting `editor.lines`.
+//!
+//! Values are parsed into the type declared in a [`Schema`], or kept as `String`.
+
+use std::{collections::HashMap, env};
+
+use crate::{
+    account::Account,
+    layer::{ParseError, Schema},
+    stg::{Setting, Stg},
+};
+
+/// Provider of an `Account` with the settings of environment variables
+///
+/// # Examples
+///
+/// ```
+/// use hashmap_settings::{account::Account,layer::{Schema,env::Environment},stg::{Setting,StgTrait}};
+///
+/// let environment = Environment::new("APP_".to_string())
+///     .schema(Schema::default().setting::<i32>("editor.lines".to_string()));
+/// let vars = [
+///     ("APP_EDITOR__LINES".to_string(), "3".to_string()),
+///     ("APP_EDITOR__WORD".to_string(), "env".to_string()),
+///     ("HOME".to_string(), "/home/user".to_string()),
+/// ];
+/// let layer = environment.account_from_vars("Environment".to_string(), vars)?;
+/// assert_eq!(layer.get(&"editor.lines".to_string()).unstg::<i32>(), Ok(3));
+/// assert_eq!(layer.get(&"editor.word".to_string()).unstg::<String>(), Ok("env".to_string()));
+/// assert_eq!(layer.hashmap().len(), 2);
+///
+/// let mut account = Account::<String,String,_>::default();
+/// account.insert("editor.lines".to_string(), 1.stg());
+/// account.push(layer, hashmap_settings::account::Valid::new_true());
+/// assert_eq!(account.get(&"editor.lines".to_string()).unstg::<i32>(), Ok(3));
+/// # Ok::<(), hashmap_settings::layer::ParseError>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct Environment {
+    prefix: String,
+    separator: String,
+    schema: Schema,
+}
+impl Environment {
+    /// Creates a new `Environment` reading the variables that start with `prefix`
+    ///
+    /// The separator is `__` and the [`Schema`] is empty.
+    #[must_use]
+    pub fn new(prefix: String) -> Self {
+        Self {
+            prefix,
+            separator: "__".to_string(),
+            schema: Schema::default(),
+        }
+    }
+    /// Sets the separator between the segments of the variable names
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::{layer::env::Environment,stg::StgTrait};
+    /// let layer = Environment::new("APP_".to_string())
+    ///     .separator("_".to_string())
+    ///     .account_from_vars("Environment".to_string(), [("APP_EDITOR_WORD".to_string(), "env".to_string())])?;
+    /// assert_eq!(layer.get(&"editor.word".to_string()).unstg::<String>(), Ok("env".to_string()));
+    /// # Ok::<(), hashmap_settings::layer::ParseError>(())
+    /// ```
+    #[must_use]
+    pub fn separator(mut self, separator: String) -> Self {
+        self.separator = separator;
+        self
+    }
+    /// Sets the [`Schema`] with the types of the settings
+    #[must_use]
+    pub fn schema(mut self, schema: Schema) -> Self {
+        self.schema = schema;
+        self
+    }
+    /// Creates an `Account` with the settings of the process environment variables
+    ///
+    /// Variables whose name or value isn't valid unicode are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ParseError`] if a value can't be parsed into the type declared in the [`Schema`].
+    pub fn account(&self, name: String) -> Result<Account<String, String, Stg>, ParseError> {
+        self.account_from_vars(
+            name,
+            env::vars_os().filter_map(|(variable, value)| {
+                Some((variable.into_string().ok()?, value.into_string().ok()?))
+            }),
+        )
+    }
+    /// Creates an `Account` with the settings of the variables provided
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ParseError`] if a value can't be parsed into the type declared in the [`Schema`].
+    pub fn account_from_vars<I: IntoIterator<Item = (String, String)>>(
+        &self,
+        name: String,
+        vars: I,
+    ) -> Result<Account<String, String, Stg>, ParseError> {
+        let mut settings = HashMap::new();
+        for (variable, value) in vars {
+            let Some(key) = self.key(&variable) else {
+                continue;
+            };
+            let value = match self.schema.parse(&key, &value) {
+                Some(result) => result?,
+                None => value.stg(),
+            };
+            settings.insert(key, value);
+        }
+        Ok(Account::new(name, true, settings, vec![]))
+    }
+    fn key(&self, variable: &str) -> Option<String> {
+        let rest = variable.strip_prefix(&self.prefix)?;
+        if rest.is_empty() {
+            return None;
+        }
+        Some(
+            rest.split(self.separator.as_str())
+                .map(str::to_lowercase)
+                .collect::<Vec<_>>()
+                .join("."),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        layer::{Schema, env::Environment},
+        stg::StgTrait,
+    };
+
+    fn vars(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter()
+            .map(|(variable, value)| ((*variable).to_string(), (*value).to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn keys() {
+        let environment = Environment::new("APP_".to_string());
+        assert_eq!(environment.key("APP_LINES"), Some("lines".to_string()));
+        assert_eq!(
+            environment.key("APP_EDITOR__COLORS__BACKGROUND"),
+            Some("editor.colors.background".to_string())
+        );
+        assert_eq!(environment.key("APP_"), None);
+        assert_eq!(environment.key("OTHER_LINES"), None);
+    }
+    #[test]
+    fn schema_types() {
+        let environment = Environment::new("APP_".to_string()).schema(
+            Schema::default()
+                .setting::<u8>("lines".to_string())
+                .setting::<bool>("wrap".to_string()),
+        );
+        let account = environment
+            .account_from_vars(
+                "Environment".to_string(),
+                vars(&[("APP_LINES", "3"), ("APP_WRAP", "true"), ("APP_WORD", "3")]),
+            )
+            .unwrap();
+        assert_eq!(account.get(&"lines".to_string()).unstg::<u8>(), Ok(3));
+        assert_eq!(account.get(&"wrap".to_string()).unstg::<bool>(), Ok(true));
+        assert_eq!(
+            account.get(&"word".to_string()).unstg::<String>(),
+            Ok("3".to_string())
+        );
+
+        let error = environment
+            .account_from_vars("Environment".to_string(), vars(&[("APP_LINES", "300")]))
+            .unwrap_err();
+        assert_eq!(error.key(), "lines");
+        assert_eq!(error.value(), "300");
+        assert_eq!(error.setting(), "u8");
+    }
+}
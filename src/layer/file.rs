@@ -0,0 +1,378 @@
+//! Files bound to child `Accounts`, that are reloaded when they change.
+//!
+//! A [`FileLayer`] binds a file to a child `Account` of a parent `Account`. Each time
+//! [`poll`](FileLayer::poll) is called the file is checked for changes, and if it changed it's parsed again and its
+//! settings replace the ones of the child `Account` with [`deep_replace_settings`](Account::deep_replace_settings),
+//! so the parent `Accounts` are updated and their [subscribers](Account::subscribe) notified of the changed settings.
+//!
+//! Changes are detected by polling the modification time and the length of the file, so `poll` is meant to be
+//! called periodically, from a timer or a loop of a thread, and works the same on every platform.
+
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use crate::account::{Account, AccountPath, DeepError};
+
+type Parser<K, V, E> = Box<dyn Fn(&str) -> Result<HashMap<K, V>, E> + Send + Sync>;
+
+/// A file bound to a child `Account`
+///
+/// # Examples
+///
+/// ```
+/// use std::{collections::HashMap, fs};
+/// use hashmap_settings::{account::{Account,AccountPath},layer::file::FileLayer};
+///
+/// let file = std::env::temp_dir().join(format!("hashmap_settings_doc_{}", std::process::id()));
+/// fs::write(&file, "lines")?;
+///
+/// let mut account = Account::<String,String,usize>::new(
+///     "Main".to_string(),
+///     true,
+///     Default::default(),
+///     vec![Account::new("Words".to_string(), true, Default::default(), vec![])],
+/// );
+/// // every word in the file is a setting with its length as the value
+/// let mut layer = FileLayer::new(&file, AccountPath::new(vec!["Words".to_string()]), |text: &str| {
+///     Ok::<_, std::convert::Infallible>(
+///         text.split_whitespace().map(|word| (word.to_string(), word.len())).collect::<HashMap<_,_>>(),
+///     )
+/// });
+/// assert_eq!(layer.poll(&mut account)?, true);
+/// assert_eq!(layer.poll(&mut account)?, false);
+/// assert_eq!(account.get(&"lines".to_string()), Some(&5));
+///
+/// fs::write(&file, "words width")?;
+/// assert_eq!(layer.poll(&mut account)?, true);
+/// assert_eq!(account.get(&"lines".to_string()), None);
+/// assert_eq!(account.get(&"width".to_string()), Some(&5));
+/// # fs::remove_file(&file)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct FileLayer<N, K, V, E> {
+    path: PathBuf,
+    account_path: AccountPath<N>,
+    parse: Parser<K, V, E>,
+    stamp: Option<(SystemTime, u64)>,
+}
+impl<N, K, V, E> FileLayer<N, K, V, E> {
+    /// Creates a `FileLayer` that binds the file at `path` to a child `Account`
+    ///
+    /// `account_path` identifies the child `Account`, and `parse` turns the contents of the file into its settings.
+    ///
+    /// The file isn't read until [`poll`](FileLayer::poll) is called.
+    pub fn new<
+        P: Into<PathBuf>,
+        F: Fn(&str) -> Result<HashMap<K, V>, E> + Send + Sync + 'static,
+    >(
+        path: P,
+        account_path: AccountPath<N>,
+        parse: F,
+    ) -> Self {
+        Self {
+            path: path.into(),
+            account_path,
+            parse: Box::new(parse),
+            stamp: None,
+        }
+    }
+    /// Returns the path of the file
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+    /// Returns the path of the child `Account`
+    #[must_use]
+    pub const fn account_path(&self) -> &AccountPath<N> {
+        &self.account_path
+    }
+}
+impl<N: PartialEq, K: Clone + Eq + std::hash::Hash, V: Clone, E> FileLayer<N, K, V, E> {
+    /// Reloads the file if it changed since the last time it was loaded, returning `true` if it was reloaded
+    ///
+    /// The first call always loads the file.
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be read, or can't be parsed, the settings of the child `Account` are left as they were and
+    /// a [`ReloadError`] is returned. A file that can't be parsed isn't parsed again until it changes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{collections::HashMap, fs, num::ParseIntError};
+    /// use hashmap_settings::{account::{Account,AccountPath},layer::file::{FileLayer,ReloadError}};
+    ///
+    /// let file = std::env::temp_dir().join(format!("hashmap_settings_poll_{}", std::process::id()));
+    /// fs::write(&file, "3")?;
+    ///
+    /// let mut account = Account::<String,&str,i32>::new(
+    ///     "Main".to_string(),
+    ///     true,
+    ///     Default::default(),
+    ///     vec![Account::new("Lines".to_string(), true, Default::default(), vec![])],
+    /// );
+    /// let mut layer = FileLayer::new(&file, AccountPath::new(vec!["Lines".to_string()]), |text: &str| {
+    ///     Ok::<_, ParseIntError>(HashMap::from([("lines", text.trim().parse()?)]))
+    /// });
+    /// layer.poll(&mut account)?;
+    /// assert_eq!(account.get(&"lines"), Some(&3));
+    ///
+    /// fs::write(&file, "three")?;
+    /// assert!(matches!(layer.poll(&mut account), Err(ReloadError::Parse(_))));
+    /// assert_eq!(account.get(&"lines"), Some(&3));
+    /// # fs::remove_file(&file)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn poll(&mut self, account: &mut Account<N, K, V>) -> Result<bool, ReloadError<E>> {
+        let metadata = fs::metadata(&self.path).map_err(ReloadError::Io)?;
+        let stamp = (
+            metadata.modified().map_err(ReloadError::Io)?,
+            metadata.len(),
+        );
+        if self.stamp == Some(stamp) {
+            return Ok(false);
+        }
+        let result = self.load(account);
+        if !matches!(result, Err(ReloadError::Io(_))) {
+            self.stamp = Some(stamp);
+        }
+        result.map(|()| true)
+    }
+    /// Loads the file, even if it didn't change
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be read, or can't be parsed, the settings of the child `Account` are left as they were and
+    /// a [`ReloadError`] is returned.
+    pub fn reload(&mut self, account: &mut Account<N, K, V>) -> Result<(), ReloadError<E>> {
+        self.stamp = None;
+        self.poll(account).map(|_| ())
+    }
+    fn load(&self, account: &mut Account<N, K, V>) -> Result<(), ReloadError<E>> {
+        let contents = fs::read_to_string(&self.path).map_err(ReloadError::Io)?;
+        let settings = (self.parse)(&contents).map_err(ReloadError::Parse)?;
+        account
+            .deep_replace_settings(settings, &self.account_path)
+            .map_err(ReloadError::Deep)?;
+        Ok(())
+    }
+}
+#[cfg(feature = "toml")]
+impl<N> FileLayer<N, String, crate::stg::Stg, crate::layer::toml::TomlError> {
+    /// Creates a `FileLayer` of a TOML file, parsed like [`Account::from_toml`]
+    ///
+    /// Requires the "toml" feature.
+    pub fn toml<P: Into<PathBuf>>(path: P, account_path: AccountPath<N>) -> Self {
+        Self::new(path, account_path, |toml: &str| {
+            Account::from_toml(String::new(), toml).map(|layer| layer.hashmap().clone())
+        })
+    }
+}
+#[cfg(feature = "json")]
+impl<N> FileLayer<N, String, crate::stg::Stg, crate::layer::json::JsonError> {
+    /// Creates a `FileLayer` of a JSON file, parsed like [`Account::from_json`]
+    ///
+    /// Requires the "json" feature.
+    pub fn json<P: Into<PathBuf>>(path: P, account_path: AccountPath<N>) -> Self {
+        Self::new(path, account_path, |json: &str| {
+            Account::from_json(String::new(), json).map(|layer| layer.hashmap().clone())
+        })
+    }
+}
+impl<N: core::fmt::Debug, K, V, E> core::fmt::Debug for FileLayer<N, K, V, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FileLayer")
+            .field("path", &self.path)
+            .field("account_path", &self.account_path)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Errors of reloading a [`FileLayer`]
+#[derive(Debug)]
+pub enum ReloadError<E> {
+    /// Error reading the file
+    Io(io::Error),
+    /// Error of a file that couldn't be parsed
+    Parse(E),
+    /// Error of a child `Account` that doesn't exist
+    Deep(DeepError),
+}
+impl<E: core::fmt::Display> core::fmt::Display for ReloadError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Io(error) => error.fmt(f),
+            Self::Parse(error) => error.fmt(f),
+            Self::Deep(error) => write!(f, "{error:?}"),
+        }
+    }
+}
+impl<E: core::fmt::Debug + core::fmt::Display> std::error::Error for ReloadError<E> {}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        fs,
+        num::ParseIntError,
+        path::PathBuf,
+        sync::{Arc, Mutex},
+    };
+
+    use crate::{
+        account::{Account, AccountPath, DeepError},
+        layer::file::{FileLayer, ReloadError},
+    };
+
+    fn temp_file(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("hashmap_settings_{name}_{}", std::process::id()))
+    }
+    /// Parses lines of `key=value`
+    fn parse(text: &str) -> Result<HashMap<String, i32>, ParseIntError> {
+        text.lines()
+            .filter_map(|line| line.split_once('='))
+            .map(|(key, value)| Ok((key.to_string(), value.parse()?)))
+            .collect()
+    }
+    fn account() -> Account<String, String, i32> {
+        Account::new(
+            "Main".to_string(),
+            true,
+            HashMap::new(),
+            vec![
+                Account::new(
+                    "Default".to_string(),
+                    true,
+                    [("lines".to_string(), 3), ("words".to_string(), 2)].into(),
+                    vec![],
+                ),
+                Account::new(
+                    "Files".to_string(),
+                    true,
+                    HashMap::new(),
+                    vec![Account::new(
+                        "Local".to_string(),
+                        true,
+                        HashMap::new(),
+                        vec![],
+                    )],
+                ),
+            ],
+        )
+    }
+
+    #[test]
+    fn reload_notifies_changed_settings() {
+        let file = temp_file("reload");
+        fs::write(&file, "lines=5\nwidth=80\n").unwrap();
+        let mut account = account();
+        let changes = Arc::new(Mutex::new(vec![]));
+        let changes_clone = Arc::clone(&changes);
+        account.subscribe_all(move |key: &String, old, new| {
+            changes_clone
+                .lock()
+                .unwrap()
+                .push((key.clone(), old.copied(), new.copied()));
+        });
+        let mut layer = FileLayer::new(&file, "Files/Local".parse().unwrap(), parse);
+
+        assert!(layer.poll(&mut account).unwrap());
+        assert!(!layer.poll(&mut account).unwrap());
+        assert_eq!(account.get(&"lines".to_string()), Some(&5));
+        assert_eq!(account.get(&"width".to_string()), Some(&80));
+        changes.lock().unwrap().sort();
+        assert_eq!(
+            changes.lock().unwrap().drain(..).collect::<Vec<_>>(),
+            vec![
+                ("lines".to_string(), Some(3), Some(5)),
+                ("width".to_string(), None, Some(80)),
+            ]
+        );
+
+        fs::write(&file, "lines=5\nwords=100\n").unwrap();
+        assert!(layer.poll(&mut account).unwrap());
+        assert_eq!(account.get(&"width".to_string()), None);
+        assert_eq!(account.get(&"words".to_string()), Some(&100));
+        changes.lock().unwrap().sort();
+        assert_eq!(
+            *changes.lock().unwrap(),
+            vec![
+                ("width".to_string(), Some(80), None),
+                ("words".to_string(), Some(2), Some(100)),
+            ]
+        );
+        assert!(account.valid().is_valid());
+
+        fs::remove_file(&file).unwrap();
+    }
+    #[test]
+    fn errors_keep_previous_layer() {
+        let file = temp_file("errors");
+        fs::write(&file, "lines=5\n").unwrap();
+        let mut account = account();
+        let mut layer = FileLayer::new(
+            &file,
+            AccountPath::new(vec!["Files".to_string(), "Local".to_string()]),
+            parse,
+        );
+        assert_eq!(layer.account_path().to_string(), "Files/Local");
+        layer.poll(&mut account).unwrap();
+
+        fs::write(&file, "lines=five\n").unwrap();
+        assert!(matches!(
+            layer.poll(&mut account),
+            Err(ReloadError::Parse(_))
+        ));
+        assert_eq!(account.get(&"lines".to_string()), Some(&5));
+        // not parsed again until it changes
+        assert!(!layer.poll(&mut account).unwrap());
+
+        fs::remove_file(&file).unwrap();
+        assert!(matches!(layer.poll(&mut account), Err(ReloadError::Io(_))));
+        assert_eq!(account.get(&"lines".to_string()), Some(&5));
+
+        fs::write(&file, "lines=7\n").unwrap();
+        assert!(layer.poll(&mut account).unwrap());
+        assert_eq!(account.get(&"lines".to_string()), Some(&7));
+
+        let mut missing =
+            FileLayer::new(&file, AccountPath::new(vec!["Missing".to_string()]), parse);
+        assert!(matches!(
+            missing.poll(&mut account),
+            Err(ReloadError::Deep(DeepError::NotFound))
+        ));
+        fs::remove_file(&file).unwrap();
+    }
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml_file() {
+        use crate::stg::{Stg, StgTrait};
+
+        let file = temp_file("toml");
+        fs::write(&file, "lines = 5\n").unwrap();
+        let mut account = Account::<String, String, Stg>::new(
+            "Main".to_string(),
+            true,
+            HashMap::new(),
+            vec![Account::new(
+                "Local".to_string(),
+                true,
+                HashMap::new(),
+                vec![],
+            )],
+        );
+        let mut layer = FileLayer::toml(&file, AccountPath::new(vec!["Local".to_string()]));
+        layer.poll(&mut account).unwrap();
+        assert_eq!(account.get(&"lines".to_string()).unstg::<i64>(), Ok(5));
+
+        fs::write(&file, "lines = \n").unwrap();
+        assert!(layer.poll(&mut account).is_err());
+        assert_eq!(account.get(&"lines".to_string()).unstg::<i64>(), Ok(5));
+        fs::remove_file(&file).unwrap();
+    }
+}
@@ -0,0 +1,255 @@
+//! JSON files as `Account` layers.
+//!
+//! Each file corresponds to the [settings](Account#settings) of one `Account`, where
+//! the JSON values are turned into `Stg`s of natural types without the need of type tags:
+//!
+//! | JSON     | `Stg`                  |
+//! |----------|------------------------|
+//! | `true`/`false` | `bool`           |
+//! | Integer  | `i64`                  |
+//! | Float    | `f64`                  |
+//! | String   | `String`               |
+//! | Array    | `Vec<Stg>`             |
+//! | Object   | `HashMap<String,Stg>`  |
+//! | `null`   | `()`                   |
+//!
+//! Settings of other types can be registered in a [`TypeRegistry`].
+//!
+//! Requires the "json" feature.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::{Map, Value};
+
+use crate::{
+    account::Account,
+    layer::plain::{PlainRef, PlainStg},
+    stg::{Setting, Stg},
+};
+
+/// Types of the settings that aren't represented by the default JSON types
+///
+/// When a setting is registered, its value will be deserialized to and serialized from the type provided,
+/// instead of the natural type of the JSON value.
+///
+/// # Examples
+///
+/// ```
+/// use hashmap_settings::{account::Account,layer::json::TypeRegistry,stg::{Setting,StgTrait}};
+/// # #[cfg(feature = "serde")]
+/// # use serde::{Deserialize, Serialize};
+///
+/// #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// #[cfg_attr(not(feature = "serde"), derive(serde::Serialize, serde::Deserialize))]
+/// #[derive(Clone, Debug, PartialEq)]
+/// struct Color {
+///     red: u8,
+///     green: u8,
+///     blue: u8,
+/// }
+/// #[cfg_attr(feature = "serde", typetag::serde)]
+/// impl Setting for Color {}
+///
+/// let mut registry = TypeRegistry::default();
+/// registry.register::<Color>("background".to_string());
+///
+/// let json = r#"{"background": {"red": 0, "green": 0, "blue": 0}, "lines": 3}"#;
+/// let account = Account::from_json_with("Theme".to_string(), json, &registry)?;
+/// assert_eq!(
+///     account.get(&"background".to_string()).unstg::<Color>(),
+///     Ok(Color { red: 0, green: 0, blue: 0 })
+/// );
+/// assert_eq!(account.get(&"lines".to_string()).unstg::<i64>(), Ok(3));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TypeRegistry {
+    types: HashMap<String, RegisteredType>,
+}
+#[derive(Clone, Copy, Debug)]
+struct RegisteredType {
+    deserialize: fn(Value) -> serde_json::Result<Stg>,
+    serialize: fn(&Stg) -> Option<serde_json::Result<Value>>,
+}
+impl TypeRegistry {
+    /// Registers the type of a setting
+    ///
+    /// Returns `true` if the setting was already registered, in which case the type is replaced.
+    pub fn register<S: Setting + Serialize + DeserializeOwned>(&mut self, setting: String) -> bool {
+        self.types
+            .insert(
+                setting,
+                RegisteredType {
+                    deserialize: |value| S::deserialize(value).map(Setting::stg),
+                    serialize: |stg| stg.downcast_ref::<S>().map(serde_json::to_value),
+                },
+            )
+            .is_some()
+    }
+    fn deserialize(&self, setting: &str, value: Value) -> serde_json::Result<Stg> {
+        match self.types.get(setting) {
+            Some(registered) => (registered.deserialize)(value),
+            None => PlainStg::deserialize(value).map(|PlainStg(stg)| stg),
+        }
+    }
+    fn serialize(&self, setting: &str, value: &Stg) -> serde_json::Result<Value> {
+        self.types
+            .get(setting)
+            .and_then(|registered| (registered.serialize)(value))
+            .unwrap_or_else(|| serde_json::to_value(PlainRef(value)))
+    }
+}
+
+impl Account<String, String, Stg> {
+    /// Creates an `Account` with the settings of a JSON object
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Deserialize`] if the document isn't a valid JSON object.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::{account::Account,stg::StgTrait};
+    /// let account = Account::from_json("Local Settings".to_string(), r#"{"lines": 3, "word": "local"}"#)?;
+    /// assert_eq!(account.get(&"lines".to_string()).unstg::<i64>(), Ok(3));
+    /// assert_eq!(account.get(&"word".to_string()).unstg::<String>(), Ok("local".to_string()));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn from_json(name: String, json: &str) -> Result<Self, JsonError> {
+        Self::from_json_with(name, json, &TypeRegistry::default())
+    }
+    /// Creates an `Account` with the settings of a JSON object, using the types of the [`TypeRegistry`]
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Deserialize`] if the document isn't a valid JSON object, or a registered
+    /// setting isn't of the registered type.
+    pub fn from_json_with(
+        name: String,
+        json: &str,
+        registry: &TypeRegistry,
+    ) -> Result<Self, JsonError> {
+        let object: Map<String, Value> =
+            serde_json::from_str(json).map_err(JsonError::Deserialize)?;
+        let settings = object
+            .into_iter()
+            .map(|(key, value)| registry.deserialize(&key, value).map(|value| (key, value)))
+            .collect::<Result<HashMap<_, _>, _>>()
+            .map_err(JsonError::Deserialize)?;
+        Ok(Self::new(name, true, settings, vec![]))
+    }
+    /// Returns a JSON object with the settings of the `Account`
+    ///
+    /// The settings are sorted by key.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Serialize`] if a setting can't be represented in JSON.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::{account::Account,stg::Setting};
+    /// let mut account = Account::<String,String,_>::default();
+    /// account.insert("word".to_string(), "local".to_string().stg());
+    /// account.insert("lines".to_string(), 3.stg());
+    /// assert_eq!(account.to_json()?, "{\n  \"lines\": 3,\n  \"word\": \"local\"\n}");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn to_json(&self) -> Result<String, JsonError> {
+        self.to_json_with(&TypeRegistry::default())
+    }
+    /// Returns a JSON object with the settings of the `Account`, using the types of the [`TypeRegistry`]
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Serialize`] if a setting can't be represented in JSON.
+    pub fn to_json_with(&self, registry: &TypeRegistry) -> Result<String, JsonError> {
+        let object = self
+            .hashmap()
+            .iter()
+            .map(|(key, value)| {
+                registry
+                    .serialize(key, value)
+                    .map(|value| (key.clone(), value))
+            })
+            .collect::<Result<Map<_, _>, _>>()
+            .map_err(JsonError::Serialize)?;
+        serde_json::to_string_pretty(&object).map_err(JsonError::Serialize)
+    }
+}
+
+/// Errors of reading and writing JSON
+#[derive(Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub enum JsonError {
+    /// Error of a document that isn't a valid JSON object
+    Deserialize(serde_json::Error),
+    /// Error of a setting that can't be represented in JSON
+    Serialize(serde_json::Error),
+}
+impl core::fmt::Display for JsonError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Deserialize(error) | Self::Serialize(error) => error.fmt(f),
+        }
+    }
+}
+impl std::error::Error for JsonError {}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        account::Account,
+        layer::json::TypeRegistry,
+        stg::{Setting, Stg, StgTrait},
+    };
+
+    #[test]
+    fn plain_round_trip() {
+        let json = r#"{"lines": 3, "word": "local"}"#;
+        let account = Account::from_json("Local".to_string(), json).unwrap();
+        assert_eq!(account.get(&"lines".to_string()).unstg::<i64>(), Ok(3));
+        let saved = account.to_json().unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&saved).unwrap(),
+            serde_json::from_str::<serde_json::Value>(json).unwrap()
+        );
+    }
+    #[test]
+    fn nested_values() {
+        let json = r#"{"paths": ["a", 1, 0.5, null], "colors": {"background": "black"}}"#;
+        let account = Account::from_json("Local".to_string(), json).unwrap();
+        assert_eq!(
+            account.get(&"paths".to_string()).unstg::<Vec<Stg>>(),
+            Ok(vec![
+                "a".to_string().stg(),
+                1_i64.stg(),
+                0.5_f64.stg(),
+                ().stg()
+            ])
+        );
+        let loaded = Account::from_json("Local".to_string(), &account.to_json().unwrap()).unwrap();
+        assert!(account == loaded);
+    }
+    #[test]
+    fn registered_type() {
+        let mut registry = TypeRegistry::default();
+        registry.register::<u8>("small".to_string());
+        let json = r#"{"small": 3, "big": 3}"#;
+        let account = Account::from_json_with("Local".to_string(), json, &registry).unwrap();
+        assert_eq!(account.get(&"small".to_string()).unstg::<u8>(), Ok(3));
+        assert_eq!(account.get(&"big".to_string()).unstg::<i64>(), Ok(3));
+        assert!(
+            Account::from_json_with("Local".to_string(), r#"{"small": 300}"#, &registry).is_err()
+        );
+        assert!(
+            account
+                .to_json_with(&registry)
+                .unwrap()
+                .contains("\"small\": 3")
+        );
+    }
+}
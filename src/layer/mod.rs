@@ -0,0 +1,42 @@
+//! Creation of [`Account`](crate::account::Account) layers from external sources.
+//!
+//! Each source creates an ordinary `Account` that can be [pushed](crate::account::Account::push)
+//! to a parent `Account` as one of its layers.
+//!
+//! [`args`] Command-line `key=value` overrides.
+//!
+//! [`env`](mod@env) Environment variables.
+//!
+#![cfg_attr(
+    feature = "toml",
+    doc = "[`toml`] TOML files, requires the \"toml\" feature."
+)]
+#![cfg_attr(
+    not(feature = "toml"),
+    doc = "`toml` TOML files, requires the \"toml\" feature."
+)]
+//!
+#![cfg_attr(
+    feature = "json",
+    doc = "[`json`] JSON files, requires the \"json\" feature."
+)]
+#![cfg_attr(
+    not(feature = "json"),
+    doc = "`json` JSON files, requires the \"json\" feature."
+)]
+//!
+//! [`file`](mod@file) Files bound to child `Accounts`, reloaded when they change.
+
+pub mod args;
+pub mod env;
+pub mod file;
+#[cfg(feature = "json")]
+pub mod json;
+#[cfg(any(feature = "toml", feature = "json"))]
+mod plain;
+mod schema;
+#[cfg(feature = "toml")]
+pub mod toml;
+
+#[doc(inline)]
+pub use self::schema::{ParseError, Schema};
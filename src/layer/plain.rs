@@ -0,0 +1,133 @@
+//! Untagged representation of [`Stg`] for self-describing formats.
+//!
+//! Values are mapped to and from `Stg`s of natural types: booleans to `bool`,
+//! integers to `i64` (or `u64` if too big), floats to `f64`, strings to `String`,
+//! arrays to `Vec<Stg>`, tables/objects to `HashMap<String,Stg>` and null to `()`.
+
+use core::fmt;
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{MapAccess, SeqAccess, Visitor},
+    ser::Error,
+};
+
+use crate::stg::{Setting, Stg};
+
+/// Deserializes into a `Stg` without the need of a type tag
+pub struct PlainStg(pub Stg);
+
+impl<'de> Deserialize<'de> for PlainStg {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(PlainVisitor)
+    }
+}
+
+struct PlainVisitor;
+
+impl<'de> Visitor<'de> for PlainVisitor {
+    type Value = PlainStg;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a setting value")
+    }
+    fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E> {
+        Ok(PlainStg(v.stg()))
+    }
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E> {
+        Ok(PlainStg(v.stg()))
+    }
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(PlainStg(
+            i64::try_from(v).map_or_else(|_| v.stg(), Setting::stg),
+        ))
+    }
+    fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E> {
+        Ok(PlainStg(v.stg()))
+    }
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(PlainStg(v.to_string().stg()))
+    }
+    fn visit_string<E>(self, v: String) -> Result<Self::Value, E> {
+        Ok(PlainStg(v.stg()))
+    }
+    fn visit_unit<E>(self) -> Result<Self::Value, E> {
+        Ok(PlainStg(().stg()))
+    }
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut vec = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+        while let Some(PlainStg(value)) = seq.next_element()? {
+            vec.push(value);
+        }
+        Ok(PlainStg(vec.stg()))
+    }
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut hashmap = HashMap::with_capacity(map.size_hint().unwrap_or_default());
+        while let Some((key, PlainStg(value))) = map.next_entry::<String, PlainStg>()? {
+            hashmap.insert(key, value);
+        }
+        Ok(PlainStg(hashmap.stg()))
+    }
+}
+
+/// Serializes a `Stg` without a type tag
+///
+/// Only `Stg`s containing the types that [`PlainStg`] deserializes into, or other integer and
+/// float types, can be serialized.
+pub struct PlainRef<'a>(pub &'a Stg);
+
+macro_rules! serialize_as {
+    ($stg:expr, $serializer:expr, $($setting:ty),* $(,)?) => {
+        $(
+            if let Some(value) = $stg.downcast_ref::<$setting>() {
+                return value.serialize($serializer);
+            }
+        )*
+    };
+}
+
+impl Serialize for PlainRef<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_as!(
+            self.0,
+            serializer,
+            bool,
+            i64,
+            f64,
+            String,
+            i8,
+            i16,
+            i32,
+            i128,
+            isize,
+            u8,
+            u16,
+            u32,
+            u64,
+            u128,
+            usize,
+            f32,
+            char,
+            (),
+        );
+        if let Some(vec) = self.0.downcast_ref::<Vec<Stg>>() {
+            return serializer.collect_seq(vec.iter().map(PlainRef));
+        }
+        if let Some(hashmap) = self.0.downcast_ref::<HashMap<String, Stg>>() {
+            return sorted(hashmap).serialize(serializer);
+        }
+        Err(S::Error::custom(format!(
+            "{:?} can't be serialized without a type tag",
+            self.0
+        )))
+    }
+}
+
+/// Returns the settings sorted by key so that the output is deterministic
+pub fn sorted(settings: &HashMap<String, Stg>) -> BTreeMap<&String, PlainRef<'_>> {
+    settings
+        .iter()
+        .map(|(key, value)| (key, PlainRef(value)))
+        .collect()
+}
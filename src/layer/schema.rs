@@ -0,0 +1,117 @@
+use core::{fmt, str::FromStr};
+use std::collections::HashMap;
+
+use crate::stg::{Setting, Stg};
+
+/// Types of the settings parsed from text
+///
+/// Sources where every value is a string, like environment variables, use a `Schema`
+/// to know the type each setting should be parsed into. Settings that aren't in the
+/// `Schema` are handled by the source, usually by keeping the value as a `String`.
+///
+/// # Examples
+///
+/// ```
+/// use hashmap_settings::{layer::Schema,stg::Setting};
+/// let schema = Schema::default()
+///     .setting::<i32>("editor.lines".to_string())
+///     .setting::<bool>("editor.wrap".to_string());
+/// assert_eq!(schema.parse("editor.lines", "3"), Some(Ok(3.stg())));
+/// assert!(schema.parse("editor.wrap", "3").is_some_and(|result| result.is_err()));
+/// assert_eq!(schema.parse("editor.word", "3"), None);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Schema {
+    settings: HashMap<String, SettingType>,
+}
+#[derive(Clone, Copy, Debug)]
+struct SettingType {
+    parse: fn(&str) -> Option<Stg>,
+    name: &'static str,
+}
+impl Schema {
+    /// Declares the type of a setting
+    ///
+    /// If the setting was already declared its type is replaced.
+    #[must_use]
+    pub fn setting<S: Setting + FromStr>(mut self, key: String) -> Self {
+        self.settings.insert(
+            key,
+            SettingType {
+                parse: |value| value.parse::<S>().ok().map(Setting::stg),
+                name: core::any::type_name::<S>(),
+            },
+        );
+        self
+    }
+    /// Returns `true` if the type of the setting was declared
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::layer::Schema;
+    /// let schema = Schema::default().setting::<i32>("lines".to_string());
+    /// assert!(schema.contains("lines"));
+    /// assert!(!schema.contains("word"));
+    /// ```
+    #[must_use]
+    pub fn contains(&self, key: &str) -> bool {
+        self.settings.contains_key(key)
+    }
+    /// Parses the value of a setting into its declared type
+    ///
+    /// Returns `None` if the type of the setting wasn't declared.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ParseError`] if the value can't be parsed into the declared type.
+    #[must_use]
+    pub fn parse(&self, key: &str, value: &str) -> Option<Result<Stg, ParseError>> {
+        self.settings.get(key).map(|setting| {
+            (setting.parse)(value)
+                .ok_or_else(|| ParseError::new(key.to_string(), value.to_string(), setting.name))
+        })
+    }
+}
+
+/// Error of a value that can't be parsed into the type of its setting
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    key: String,
+    value: String,
+    setting: &'static str,
+}
+impl ParseError {
+    pub(crate) const fn new(key: String, value: String, setting: &'static str) -> Self {
+        Self {
+            key,
+            value,
+            setting,
+        }
+    }
+    /// Returns the key of the setting
+    #[must_use]
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+    /// Returns the value that couldn't be parsed
+    #[must_use]
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+    /// Returns the name of the type the value should have been parsed into
+    #[must_use]
+    pub const fn setting(&self) -> &'static str {
+        self.setting
+    }
+}
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid value {:?} for setting {:?}, expected {}",
+            self.value, self.key, self.setting
+        )
+    }
+}
+impl std::error::Error for ParseError {}
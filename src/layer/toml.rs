@@ -0,0 +1,244 @@
+//! TOML files as `Account` layers.
+//!
+//! Each file corresponds to the [settings](Account#settings) of one `Account`, where
+//! the TOML values are turned into `Stg`s of natural types without the need of type tags:
+//!
+//! | TOML     | `Stg`                  |
+//! |----------|------------------------|
+//! | Boolean  | `bool`                 |
+//! | Integer  | `i64`                  |
+//! | Float    | `f64`                  |
+//! | String   | `String`               |
+//! | Array    | `Vec<Stg>`             |
+//! | Table    | `HashMap<String,Stg>`  |
+//!
+//! A directory of files can be loaded with [`Account::load_toml_dir`] where each file is a child `Account`.
+//!
+//! Requires the "toml" feature.
+
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    account::{Account, Valid},
+    layer::plain::{PlainStg, sorted},
+    stg::Stg,
+};
+
+impl Account<String, String, Stg> {
+    /// Creates an `Account` with the settings of a TOML document
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TomlError::Deserialize`] if the document isn't valid TOML.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::{account::Account,stg::{Stg,StgTrait}};
+    /// let account = Account::from_toml(
+    ///     "Global Settings".to_string(),
+    ///     r#"
+    ///         lines = 3
+    ///         word = "global"
+    ///
+    ///         [colors]
+    ///         background = "black"
+    ///     "#,
+    /// )?;
+    /// assert_eq!(account.get(&"lines".to_string()).unstg::<i64>(), Ok(3));
+    /// assert_eq!(account.get(&"word".to_string()).unstg::<String>(), Ok("global".to_string()));
+    /// let colors: std::collections::HashMap<String, Stg> = account.get(&"colors".to_string()).unstg_panic();
+    /// assert_eq!(colors.get("background").unstg::<String>(), Ok("black".to_string()));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn from_toml(name: String, toml: &str) -> Result<Self, TomlError> {
+        let settings: HashMap<String, PlainStg> =
+            ::toml::from_str(toml).map_err(TomlError::Deserialize)?;
+        Ok(Self::new(
+            name,
+            true,
+            settings
+                .into_iter()
+                .map(|(key, PlainStg(value))| (key, value))
+                .collect(),
+            vec![],
+        ))
+    }
+    /// Returns a TOML document with the settings of the `Account`
+    ///
+    /// The settings are sorted by key.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TomlError::Serialize`] if a setting can't be represented in TOML.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::{account::Account,stg::Setting};
+    /// let mut account = Account::<String,String,_>::default();
+    /// account.insert("word".to_string(), "local".to_string().stg());
+    /// account.insert("lines".to_string(), 3.stg());
+    /// assert_eq!(account.to_toml()?, "lines = 3\nword = \"local\"\n");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn to_toml(&self) -> Result<String, TomlError> {
+        ::toml::to_string(&sorted(self.hashmap())).map_err(TomlError::Serialize)
+    }
+    /// Creates an `Account` whose child `Accounts` are the TOML files in a directory
+    ///
+    /// Files with the "toml" extension are pushed in order of their file name, so the last
+    /// file will be the top layer. The name of each child `Account` is the file name without the extension.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TomlError::Io`] if the directory or a file can't be read and
+    /// [`TomlError::Deserialize`] if a file isn't valid TOML.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use hashmap_settings::{account::Account,stg::StgTrait};
+    /// // "settings/00-default.toml" contains "lines = 3"
+    /// // "settings/10-local.toml" contains "lines = 5"
+    /// let account = Account::load_toml_dir("Settings".to_string(), "settings")?;
+    /// assert_eq!(account.accounts_names(), vec!["00-default", "10-local"]);
+    /// assert_eq!(account.get(&"lines".to_string()).unstg::<i64>(), Ok(5));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn load_toml_dir<P: AsRef<Path>>(name: String, path: P) -> Result<Self, TomlError> {
+        let mut files = fs::read_dir(path)
+            .map_err(TomlError::Io)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<PathBuf>, _>>()
+            .map_err(TomlError::Io)?;
+        files.retain(|file| file.is_file() && file.extension().is_some_and(|x| x == "toml"));
+        files.sort();
+        let mut account = Self::new(name, true, HashMap::new(), vec![]);
+        for file in files {
+            let name = file
+                .file_stem()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let toml = fs::read_to_string(&file).map_err(TomlError::Io)?;
+            account.push(Self::from_toml(name, &toml)?, Valid::new_true());
+        }
+        Ok(account)
+    }
+    /// Writes each child `Account` to a TOML file in a directory
+    ///
+    /// The files are named after the child `Accounts` with the "toml" extension, so the directory can
+    /// be loaded again with [`load_toml_dir`](Account::load_toml_dir). Only the settings of the child `Accounts`
+    /// are saved, so their activity and their own child `Accounts` are lost.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TomlError::Io`] if a file can't be written and
+    /// [`TomlError::Serialize`] if a setting can't be represented in TOML.
+    pub fn save_toml_dir<P: AsRef<Path>>(&self, path: P) -> Result<(), TomlError> {
+        let path = path.as_ref();
+        fs::create_dir_all(path).map_err(TomlError::Io)?;
+        for account in self.accounts() {
+            fs::write(
+                path.join(format!("{}.toml", account.name())),
+                account.to_toml()?,
+            )
+            .map_err(TomlError::Io)?;
+        }
+        Ok(())
+    }
+}
+
+/// Errors of loading and saving TOML files
+#[derive(Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub enum TomlError {
+    /// Error reading or writing a file
+    Io(io::Error),
+    /// Error of a document that isn't valid TOML
+    Deserialize(::toml::de::Error),
+    /// Error of a setting that can't be represented in TOML
+    Serialize(::toml::ser::Error),
+}
+impl core::fmt::Display for TomlError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Io(error) => error.fmt(f),
+            Self::Deserialize(error) => error.fmt(f),
+            Self::Serialize(error) => error.fmt(f),
+        }
+    }
+}
+impl std::error::Error for TomlError {}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::{
+        account::Account,
+        stg::{Setting, Stg, StgTrait},
+    };
+
+    #[test]
+    fn round_trip() {
+        let toml = r#"
+            lines = 3
+            ratio = 0.5
+            word = "local"
+            wrap = true
+            paths = ["a", "b"]
+
+            [colors]
+            background = "black"
+            sizes = [1, 2]
+        "#;
+        let account = Account::from_toml("Layer".to_string(), toml).unwrap();
+        assert_eq!(account.get(&"ratio".to_string()).unstg::<f64>(), Ok(0.5));
+        assert_eq!(account.get(&"wrap".to_string()).unstg::<bool>(), Ok(true));
+        assert_eq!(
+            account.get(&"paths".to_string()).unstg::<Vec<Stg>>(),
+            Ok(vec!["a".to_string().stg(), "b".to_string().stg()])
+        );
+        let saved = account.to_toml().unwrap();
+        let loaded = Account::from_toml("Layer".to_string(), &saved).unwrap();
+        assert!(account == loaded);
+    }
+    #[test]
+    fn unsupported_type() {
+        let mut account = Account::<String, String, Stg>::default();
+        account.insert("time".to_string(), std::time::Duration::ZERO.stg());
+        assert!(account.to_toml().is_err());
+    }
+    #[test]
+    fn directory_layers() {
+        let dir =
+            std::env::temp_dir().join(format!("hashmap_settings_toml_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("00-default.toml"), "lines = 3\nword = \"default\"").unwrap();
+        fs::write(dir.join("10-global.toml"), "word = \"global\"").unwrap();
+        fs::write(dir.join("20-local.toml"), "lines = 5").unwrap();
+        fs::write(dir.join("notes.txt"), "not a layer").unwrap();
+
+        let account = Account::load_toml_dir("Settings".to_string(), &dir).unwrap();
+        assert_eq!(
+            account.accounts_names(),
+            vec!["00-default", "10-global", "20-local"]
+        );
+        assert_eq!(account.get(&"lines".to_string()).unstg::<i64>(), Ok(5));
+        assert_eq!(
+            account.get(&"word".to_string()).unstg::<String>(),
+            Ok("global".to_string())
+        );
+
+        let saved = dir.join("saved");
+        account.save_toml_dir(&saved).unwrap();
+        let loaded = Account::load_toml_dir("Settings".to_string(), &saved).unwrap();
+        assert!(account == loaded);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -28,8 +28,9 @@
 //!
 //! ### Drawbacks
 //!
-//! 1. Each `Account` holds a copy of the settings present in it's child Accounts, so there is a memory cost, but its
-//!    [planned](https://github.com/OxidizedLoop/HashMapSettings/issues/28) for it to be changed to a reference to the value instead.
+//! 1. Each `Account` holds a copy of the settings present in it's child Accounts, so there is a memory cost.
+//!    This can be avoided by storing [`Rc<Stg>`](std::rc::Rc) instead of `Stg`, so that parent Accounts
+//!    hold a reference to the value instead.
 //!
 //! 2. Having to internally do a [`HashMap`](std::collections::HashMap)'s `.get()` will most likely be slower than alternatives.
 //!
@@ -156,29 +157,52 @@ println!("{inactive_word}");
 //! ```
 
 #![doc(test(attr(deny(warnings))))] //no warnings in tests
+extern crate self as hashmap_settings; //allows derive macros to be used inside the crate
 /// [`Account`] and other related elements.
 pub mod account;
+pub mod layer;
 pub mod stg;
 pub mod prelude {
     //! Prelude containing everything that will likely be needed while using `Account`
     //!
     //! This includes everything in the crate except the trait [`Incrementable`](crate::account::Incrementable)
+    #[cfg(feature = "shared")]
     #[doc(inline)]
-    pub use crate::account::{Account, DeepError, Valid};
+    pub use crate::account::SharedAccount;
+    #[cfg(feature = "async")]
     #[doc(inline)]
-    pub use crate::stg::{Setting, Stg, StgError, StgTrait};
+    pub use crate::account::Watch;
+    #[doc(inline)]
+    pub use crate::account::{
+        Account, AccountBuilder, AccountPath, AccountPathError, BuildError, Change, Condition,
+        ConditionError, Conflict, DeepError, DeepNames, Diff, History, Merge, MergePolicy, Origin,
+        Patch, Snapshot, SubscriptionId, Transaction, Valid,
+    };
+    #[doc(inline)]
+    #[cfg(feature = "shared")]
+    #[doc(inline)]
+    pub use crate::stg::SendStg;
+    #[doc(inline)]
+    pub use crate::stg::{Key, Setting, Settings, SettingsError, Stg, StgError, StgTrait};
 }
 
 // inline for docs
 #[doc(inline)]
 pub use self::{account::Account, stg::Stg};
 
+#[doc(hidden)]
+pub mod __private {
+    //! Dependencies used by the code generated by the derive macros, not public API.
+    #[cfg(feature = "serde")]
+    pub use typetag;
+}
+
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
+    use std::{collections::HashMap, rc::Rc};
 
     use crate::{
-        account::Account,
+        account::{Account, AccountBuilder, BuildError},
         prelude::Valid,
         stg::{Setting, Stg, StgError, StgTrait},
     };
@@ -338,6 +362,122 @@ mod tests {
         // add #[typetag::serde] if serde feature is activated
         impl Setting for MyType {}
     }
+    #[cfg(feature = "derive")]
+    #[test]
+    fn derive_setting() {
+        #[cfg(feature = "serde")]
+        use serde::{Deserialize, Serialize};
+
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        #[derive(Clone, Debug, PartialEq, Setting)]
+        struct Derived {
+            value: i32,
+        }
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        #[derive(Clone, Debug, PartialEq, Setting)]
+        #[setting(name = "renamed")]
+        enum DerivedEnum {
+            Value,
+        }
+
+        let mut account = Account::<(), &str, Stg>::default();
+        account.insert("derived", Derived { value: 42 }.stg());
+        account.insert("enum", DerivedEnum::Value.stg());
+        assert_eq!(account.get(&"derived").unstg(), Ok(Derived { value: 42 }));
+        assert_eq!(account.get(&"enum").unstg(), Ok(DerivedEnum::Value));
+        #[cfg(feature = "serde")]
+        {
+            assert_eq!(Derived { value: 42 }.typetag_name(), "Derived");
+            assert_eq!(DerivedEnum::Value.typetag_name(), "renamed");
+        }
+    }
+    #[cfg(feature = "derive")]
+    #[test]
+    fn derive_settings() {
+        use crate::stg::{Settings, SettingsError};
+
+        fn default_word() -> String {
+            "default".to_string()
+        }
+        #[derive(Debug, PartialEq, Settings)]
+        struct EditorSettings {
+            lines: i32,
+            #[setting(default = "default_word")]
+            word: String,
+            #[setting(rename = "word repetition")]
+            word_repetition: u8,
+        }
+
+        let mut account = Account::<String, String, Stg>::new(
+            "Parent".to_string(),
+            true,
+            HashMap::new(),
+            vec![Account::new(
+                "Editor".to_string(),
+                true,
+                HashMap::new(),
+                vec![],
+            )],
+        );
+        assert_eq!(
+            EditorSettings::from_account(&account),
+            Err(SettingsError::new(vec![
+                ("lines", StgError::None),
+                ("word repetition", StgError::None),
+            ]))
+        );
+        account.insert("lines".to_string(), "3".to_string().stg());
+        account.insert("word".to_string(), 3.stg());
+        assert_eq!(
+            EditorSettings::from_account(&account),
+            Err(SettingsError::new(vec![
+                ("lines", StgError::WrongType),
+                ("word", StgError::WrongType),
+                ("word repetition", StgError::None),
+            ]))
+        );
+
+        let settings = EditorSettings {
+            lines: 3,
+            word: "local".to_string(),
+            word_repetition: 2,
+        };
+        settings
+            .write_to(&mut account, &mut vec![&"Editor".to_string()])
+            .unwrap();
+        assert_eq!(
+            account
+                .deep(&mut vec![&"Editor".to_string()])
+                .unwrap()
+                .get(&"word repetition".to_string()),
+            Some(&2_u8.stg())
+        );
+        assert_eq!(EditorSettings::from_account(&account), Ok(settings));
+        assert_eq!(
+            EditorSettings {
+                lines: 0,
+                word: String::new(),
+                word_repetition: 0,
+            }
+            .write_to(&mut account, &mut vec![&"Missing".to_string()]),
+            Err(crate::account::DeepError::NotFound)
+        );
+
+        let mut policy = Account::new("Policy".to_string(), true, HashMap::new(), vec![]);
+        policy.lock("word repetition".to_string());
+        account.insert_account(0, policy, crate::account::Valid::new_true());
+        let before = account.clone();
+        assert_eq!(
+            EditorSettings {
+                lines: 5,
+                word: "new".to_string(),
+                word_repetition: 4,
+            }
+            .write_to(&mut account, &mut vec![&"Editor".to_string()]),
+            Err(crate::account::DeepError::Locked)
+        );
+        assert_eq!(account, before);
+    }
     #[test]
     fn account_new() {
         let mut account1 = Account::new(
@@ -360,4 +500,137 @@ mod tests {
         );
         assert!(account1 == account2);
     }
+    #[test]
+    fn pop_updates_settings() {
+        let mut account = Account::<String, &str, i32>::new(
+            "Main".to_string(),
+            true,
+            HashMap::new(),
+            vec![Account::new(
+                "Default".to_string(),
+                true,
+                [("lines", 3)].into(),
+                vec![],
+            )],
+        );
+        account.push(
+            Account::new("Local".to_string(), true, [("lines", 5)].into(), vec![]),
+            Valid::new_true(),
+        );
+        assert_eq!(account.get(&"lines"), Some(&5));
+        assert!(account.valid().is_valid());
+
+        account.pop(Valid::new_true());
+        assert_eq!(account.get(&"lines"), Some(&3));
+        assert!(account.valid().is_valid());
+        account.pop(Valid::new_true());
+        assert_eq!(account.get(&"lines"), None);
+        assert!(account.valid().is_valid());
+    }
+    #[test]
+    fn rc_storage_is_shared() {
+        let depth = 5;
+        let value = Rc::new("theme table".to_string().stg());
+        let mut account = Account::<String, &str, Rc<Stg>>::new(
+            "0".to_string(),
+            true,
+            HashMap::from([("theme", Rc::clone(&value))]),
+            vec![],
+        );
+        for level in 1..depth {
+            account = Account::new(level.to_string(), true, HashMap::new(), vec![account]);
+        }
+        //one reference per Account, and the one held by `value`
+        assert_eq!(Rc::strong_count(&value), depth + 1);
+        assert!(Rc::ptr_eq(account.get(&"theme").unwrap(), &value));
+        assert_eq!(
+            account.get(&"theme").unstg::<String>(),
+            Ok("theme table".to_string())
+        );
+
+        let new_value = Rc::new("new theme table".to_string().stg());
+        account
+            .deep_insert(
+                &"theme",
+                Rc::clone(&new_value),
+                &mut vec![
+                    &"0".to_string(),
+                    &"1".to_string(),
+                    &"2".to_string(),
+                    &"3".to_string(),
+                ],
+            )
+            .unwrap();
+        assert_eq!(Rc::strong_count(&value), 1);
+        assert_eq!(Rc::strong_count(&new_value), depth + 1);
+        assert!(Rc::ptr_eq(account.get(&"theme").unwrap(), &new_value));
+    }
+    #[test]
+    fn account_builder() {
+        let built = AccountBuilder::<String, &str, Stg>::new()
+            .name("Parent".to_string())
+            .child(
+                AccountBuilder::new()
+                    .name("Default".to_string())
+                    .setting("lines", 3.stg())
+                    .child(
+                        AccountBuilder::new()
+                            .name("Colors".to_string())
+                            .setting("background", "black".to_string().stg()),
+                    ),
+            )
+            .child(
+                AccountBuilder::new()
+                    .name("Local".to_string())
+                    .setting("lines", 5.stg()),
+            )
+            .build()
+            .unwrap();
+        let mut account = Account::<String, &str, Stg>::default();
+        account.rename("Parent".to_string());
+        account.push(
+            Account::new(
+                "Default".to_string(),
+                true,
+                HashMap::from([("lines", 3.stg())]),
+                vec![Account::new(
+                    "Colors".to_string(),
+                    true,
+                    HashMap::from([("background", "black".to_string().stg())]),
+                    vec![],
+                )],
+            ),
+            Valid::new_true(),
+        );
+        account.push(
+            Account::new(
+                "Local".to_string(),
+                true,
+                HashMap::from([("lines", 5.stg())]),
+                vec![],
+            ),
+            Valid::new_true(),
+        );
+        assert!(built == account);
+        assert_eq!(built.get(&"lines").unstg::<i32>(), Ok(5));
+        assert_eq!(
+            built.get(&"background").unstg::<String>(),
+            Ok("black".to_string())
+        );
+    }
+    #[test]
+    fn account_builder_duplicate_name() {
+        let result = AccountBuilder::<String, &str, Stg>::new()
+            .child(
+                AccountBuilder::new()
+                    .name("Layer".to_string())
+                    .child(AccountBuilder::new().name("Repeated".to_string()))
+                    .child(AccountBuilder::new().name("Repeated".to_string())),
+            )
+            .build();
+        assert_eq!(
+            result,
+            Err(BuildError::DuplicateName("Repeated".to_string()))
+        );
+    }
 }
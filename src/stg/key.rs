@@ -0,0 +1,155 @@
+use core::{
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+};
+
+use crate::{
+    account::Account,
+    stg::{Setting, Stg, StgError, StgTrait},
+};
+
+/// Typed key of a setting
+///
+/// A `Key<K,T>` is a wrapper around the key `K` of a setting that also holds the type `T`
+/// the setting is expected to have. Using it with [`get_typed`](Account::get_typed) and
+/// [`insert_typed`](Account::insert_typed) removes the need of specifying the type
+/// when calling [`unstg`](StgTrait::unstg), and makes inserting a value of the wrong type a compile error.
+///
+/// # Examples
+///
+/// ```
+/// use hashmap_settings::{account::Account,stg::{Key,Stg,StgError}};
+///
+/// const LINES: Key<&str, i32> = Key::new("lines");
+///
+/// let mut account = Account::<(),&str,Stg>::default();
+/// account.insert_typed(&LINES, 3);
+/// assert_eq!(account.get_typed(&LINES), Ok(3));
+/// # Ok::<(),StgError>(())
+/// ```
+///
+/// ```compile_fail
+/// use hashmap_settings::{account::Account,stg::{Key,Stg}};
+///
+/// const LINES: Key<&str, i32> = Key::new("lines");
+///
+/// let mut account = Account::<(),&str,Stg>::default();
+/// account.insert_typed(&LINES, "3".to_string());
+/// ```
+pub struct Key<K, T> {
+    name: K,
+    setting: PhantomData<fn() -> T>,
+}
+impl<K, T: Setting> Key<K, T> {
+    /// Creates a new `Key`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::stg::Key;
+    /// let key: Key<&str, bool> = Key::new("Today is good");
+    /// assert_eq!(key.name(), &"Today is good");
+    /// ```
+    #[must_use]
+    pub const fn new(name: K) -> Self {
+        Self {
+            name,
+            setting: PhantomData,
+        }
+    }
+}
+impl<K, T> Key<K, T> {
+    /// Returns a reference to the key `K` of the setting
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::stg::Key;
+    /// let key: Key<String, i32> = Key::new("Number of trees".to_string());
+    /// assert_eq!(key.name(), "Number of trees");
+    /// ```
+    #[must_use]
+    pub const fn name(&self) -> &K {
+        &self.name
+    }
+}
+impl<K: Clone, T> Clone for Key<K, T> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            setting: PhantomData,
+        }
+    }
+}
+impl<K: Copy, T> Copy for Key<K, T> {}
+impl<K: Debug, T> Debug for Key<K, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Key")
+            .field("name", &self.name)
+            .field("setting", &core::any::type_name::<T>())
+            .finish()
+    }
+}
+impl<K: PartialEq, T> PartialEq for Key<K, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+impl<K: Eq, T> Eq for Key<K, T> {}
+impl<K: Hash, T> Hash for Key<K, T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+    }
+}
+
+impl<N, K: Clone + Eq + Hash, V: From<Stg>> Account<N, K, V>
+where
+    for<'a> Option<&'a V>: StgTrait,
+{
+    /// Returns the value corresponding to a typed [`Key`]
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StgError::None`] if the setting isn't present and [`StgError::WrongType`] if
+    /// the setting is present but isn't of type `T`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::{account::Account,stg::{Key,Setting,Stg,StgError}};
+    ///
+    /// const LINES: Key<&str, i32> = Key::new("lines");
+    /// const WORD: Key<&str, String> = Key::new("word");
+    ///
+    /// let mut account = Account::<(),&str,Stg>::default();
+    /// account.insert("lines", 3.stg());
+    /// account.insert("word", 3.stg());
+    ///
+    /// assert_eq!(account.get_typed(&LINES), Ok(3));
+    /// assert_eq!(account.get_typed(&WORD), Err(StgError::WrongType));
+    /// assert_eq!(account.get_typed(&Key::<_, bool>::new("missing")), Err(StgError::None));
+    /// ```
+    pub fn get_typed<T: Setting>(&self, key: &Key<K, T>) -> Result<T, StgError> {
+        self.get(key.name()).unstg()
+    }
+    /// Inserts the value of a typed [`Key`] into the map.
+    ///
+    /// Equivalent to calling [`insert`](Account::insert) with the key's name and the value turned into [`Stg`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hashmap_settings::{account::Account,stg::{Key,Setting,Stg}};
+    ///
+    /// const LINES: Key<&str, i32> = Key::new("lines");
+    ///
+    /// let mut account = Account::<(),&str,Stg>::default();
+    /// assert_eq!(account.insert_typed(&LINES, 3), None);
+    /// assert_eq!(account.insert_typed(&LINES, 4), Some(3.stg()));
+    /// assert_eq!(account.get(&"lines"), Some(&4.stg()));
+    /// ```
+    pub fn insert_typed<T: Setting>(&mut self, key: &Key<K, T>, value: T) -> Option<V> {
+        self.insert(key.name().clone(), value.stg().into())
+    }
+}
@@ -8,6 +8,19 @@
 //!
 //! [`StgTrait`] Trait implement
 //!
+//! [`Key`] Typed key of a setting
+//!
+//! [`Settings`] Trait for structs whose fields are settings
+//!
+#![cfg_attr(
+    feature = "shared",
+    doc = "[`SendStg`] Thread-safe version of `Stg`, requires the \"shared\" feature."
+)]
+#![cfg_attr(
+    not(feature = "shared"),
+    doc = "`SendStg` Thread-safe version of `Stg`, requires the \"shared\" feature."
+)]
+//!
 //!
 //! # Example use of `Stg` in an [`Account`](crate::account::Account):
 //!
@@ -33,24 +46,56 @@
 //! Ok::<(),StgError>(())
 //! ```
 
+mod key;
+#[cfg(feature = "shared")]
+mod send_stg;
 ///module containing implementations of `Setting` for rust types
 pub mod setting_implementations;
+mod settings;
 
 use core::fmt::Debug;
-use std::any::Any;
+use std::{any::Any, rc::Rc};
 
 use dyn_clone::DynClone;
 use dyn_ord::DynEq;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+#[doc(inline)]
+pub use self::key::Key;
+#[cfg(feature = "shared")]
+#[doc(inline)]
+pub use self::send_stg::SendStg;
+#[doc(hidden)]
+pub use self::settings::__private;
+#[doc(inline)]
+pub use self::settings::{Settings, SettingsError};
+#[cfg(feature = "derive")]
+#[doc(inline)]
+pub use hashmap_settings_derive::{Setting, Settings};
+
 /// Required trait for conversion to abstract type [Stg]
 ///
 /// For a Type to be able to implement Setting it needs to implement the traits
 /// [Clone], [Debug], [PartialEq] (as well as [Deserialize](https://docs.rs/serde/latest/serde/trait.Deserialize.html) and [Serialize](https://docs.rs/serde/latest/serde/trait.Serialize.html) if the "serde" feature is activated )
 ///
-/// In the [future](https://github.com/OxidizedLoop/HashMapSettings/issues/1) you will be able to derive Setting,
-/// but for now you can do it by adding the following lines:
+/// With the "derive" feature activated `Setting` can be derived, this will also add the
+/// `typetag` registration needed when the "serde" feature is activated:
+/// ```
+/// # #[cfg(feature = "derive")]
+/// # {
+/// use hashmap_settings::stg::Setting;
+/// # #[cfg(feature = "serde")]
+/// # use serde::{Deserialize, Serialize};
+///
+/// # #[allow(dead_code)]
+/// # #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// #[derive(Clone, Debug, PartialEq, Setting)]
+/// pub struct MyType{}
+/// # }
+/// ```
+///
+/// Otherwise it can be implemented by adding the following lines:
 /// ```
 /// # use hashmap_settings::stg::Setting;
 /// # #[cfg(feature = "serde")]
@@ -131,6 +176,33 @@ impl PartialEq for Box<dyn Setting> {
 /// let bool: bool = hashmap.get("bool").unstg()?;
 /// # Ok::<(),StgError>(())
 /// ```
+///
+/// `StgTrait` is also implemented for `Option<&Rc<Stg>>`, so an [`Account`](crate::account::Account)
+/// can store [`Rc<Stg>`] values. Parent `Accounts` will then hold a reference to the value
+/// of their child `Accounts` instead of a copy of it. As `Stg` isn't [`Send`], an `Account` of `Stg` values
+/// stays on one thread, so `Rc` is enough to share them.
+///
+///  ```
+/// use std::rc::Rc;
+/// use hashmap_settings::{account::{Account,Valid},stg::{Setting,Stg,StgError,StgTrait}};
+/// let mut account = Account::<String,&str,Rc<Stg>>::default();
+/// account.push(
+///     Account::new(
+///         "Child".to_string(),
+///         true,
+///         [("word", Rc::new("shared".to_string().stg()))].into(),
+///         vec![],
+///     ),
+///     Valid::new_true(),
+/// );
+/// assert!(Rc::ptr_eq(
+///     account.get(&"word").unwrap(),
+///     account.accounts()[0].get(&"word").unwrap(),
+/// ));
+/// let word: String = account.get(&"word").unstg()?;
+/// assert_eq!(word, "shared");
+/// # Ok::<(),StgError>(())
+/// ```
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Clone, Debug)]
 #[must_use]
@@ -226,6 +298,14 @@ impl Stg {
         let x: Box<dyn Any> = self.value;
         *x.downcast().unwrap()
     }
+    pub(crate) fn downcast_ref<S: Setting>(&self) -> Option<&S> {
+        let x: &dyn Any = self.value.as_ref();
+        x.downcast_ref()
+    }
+    pub(crate) fn downcast_mut<S: Setting>(&mut self) -> Option<&mut S> {
+        let x: &mut dyn Any = self.value.as_mut();
+        x.downcast_mut()
+    }
 }
 #[cfg_attr(feature = "serde", typetag::serde)]
 impl Setting for Stg {}
@@ -247,6 +327,14 @@ impl StgTrait for Option<&Stg> {
         self.unwrap().clone().unstg_panic()
     }
 }
+impl StgTrait for Option<&Rc<Stg>> {
+    fn unstg<S: Setting>(self) -> Result<S, StgError> {
+        self.map(AsRef::as_ref).unstg()
+    }
+    fn unstg_panic<S: Setting>(self) -> S {
+        self.map(AsRef::as_ref).unstg_panic()
+    }
+}
 
 /// [`Stg`] container converter trait
 ///
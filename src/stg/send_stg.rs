@@ -0,0 +1,89 @@
+use std::{any::Any, sync::Arc};
+
+use dyn_ord::DynEq;
+
+use crate::stg::{Setting, StgError, StgTrait};
+
+/// Thread-safe version of [`Stg`](crate::stg::Stg)
+///
+/// `Stg` can hold any type implementing [`Setting`], so it is neither [`Send`] nor [`Sync`] and an
+/// [`Account`](crate::account::Account) of `Stg` values can't be shared between threads. `SendStg` only holds
+/// values that are `Send` and `Sync`, so an `Account` of `SendStg` values can be used with a
+/// [`SharedAccount`](crate::account::SharedAccount).
+///
+/// The value is kept behind an [`Arc`], so cloning a `SendStg`, like parent `Accounts` do with the values
+/// of their child `Accounts`, doesn't copy it.
+///
+/// `SendStg` can't be serialized, as the values are only registered for serialization as `dyn Setting`.
+///
+/// Requires the "shared" feature.
+///
+/// # Examples
+///
+/// ```
+/// use hashmap_settings::{account::Account,stg::{SendStg,StgError,StgTrait}};
+///
+/// let mut account = Account::<(),&str,SendStg>::default();
+/// account.insert("theme", SendStg::new("dark".to_string()));
+/// account.insert("lines", SendStg::new(3));
+///
+/// assert_eq!(account.get(&"theme").unstg::<String>()?, "dark");
+/// assert_eq!(account.get(&"lines").unstg::<i32>()?, 3);
+/// assert_eq!(account.get(&"lines").unstg::<bool>(), Err(StgError::WrongType));
+/// # Ok::<(),StgError>(())
+/// ```
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct SendStg {
+    value: Arc<dyn Setting + Send + Sync>,
+}
+impl SendStg {
+    /// Creates a `SendStg` holding `value`
+    pub fn new<S: Setting + Send + Sync>(value: S) -> Self {
+        Self {
+            value: Arc::new(value),
+        }
+    }
+    fn downcast_ref<S: Setting>(&self) -> Option<&S> {
+        let x: &dyn Any = self.value.as_ref();
+        x.downcast_ref()
+    }
+}
+impl PartialEq for SendStg {
+    fn eq(&self, other: &Self) -> bool {
+        let x: &dyn DynEq = self.value.as_ref();
+        let y: &dyn DynEq = other.value.as_ref();
+        x == y
+    }
+}
+impl StgTrait for Option<&SendStg> {
+    fn unstg<S: Setting>(self) -> Result<S, StgError> {
+        self.ok_or(StgError::None)?
+            .downcast_ref()
+            .map(dyn_clone::clone)
+            .ok_or(StgError::WrongType)
+    }
+    fn unstg_panic<S: Setting>(self) -> S {
+        dyn_clone::clone(self.unwrap().downcast_ref().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::stg::{SendStg, StgError, StgTrait};
+
+    #[test]
+    fn values_are_shared_and_compared() {
+        let theme = SendStg::new("dark".to_string());
+        let clone = theme.clone();
+        assert!(std::sync::Arc::ptr_eq(&theme.value, &clone.value));
+        assert_eq!(theme, clone);
+        assert_eq!(theme, SendStg::new("dark".to_string()));
+        assert_ne!(theme, SendStg::new("light".to_string()));
+        assert_ne!(SendStg::new(1_i32), SendStg::new(1_i64));
+
+        assert_eq!(Some(&theme).unstg::<String>(), Ok("dark".to_string()));
+        assert_eq!(Some(&theme).unstg::<i32>(), Err(StgError::WrongType));
+        assert_eq!(None::<&SendStg>.unstg::<String>(), Err(StgError::None));
+    }
+}
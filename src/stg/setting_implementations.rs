@@ -7,6 +7,7 @@ followed by commenting out any types that couldn't easily be added for the follo
 
 types not implemented:
 types that include any generic parameter. as #[cfg_attr(feature = "serde", typetag::serde)] can't be added.
+(Vec<Stg> and HashMap<String,Stg> are implemented as they are needed to represent arrays and tables of untagged formats)
 types in rust unstable feature: !
 types in std::sync::atomic as they don't implement PartialEq needed for DynEq
 types that had some sort of lifetime error: str, std::path::Path, [u8], serde::de::IgnoredAny
@@ -110,6 +111,11 @@ impl Setting for std::sync::atomic::AtomicU32{}//Available on crate feature // s
 impl Setting for std::sync::atomic::AtomicU64{}//Available on crate feature // std and target_has_atomic="64"
 impl Setting for std::sync::atomic::AtomicUsize{}//Available on crate feature // std and target_has_atomic="ptr"
 */
+#[cfg_attr(feature = "serde", typetag::serde(name = "Vec<Stg>"))]
+impl Setting for Vec<crate::stg::Stg> {}
+#[cfg_attr(feature = "serde", typetag::serde(name = "HashMap<String,Stg>"))]
+#[allow(clippy::implicit_hasher)]
+impl Setting for std::collections::HashMap<String, crate::stg::Stg> {}
 #[cfg_attr(feature = "serde", typetag::serde)]
 impl Setting for std::time::Duration {}
 #[cfg_attr(feature = "serde", typetag::serde)]
@@ -0,0 +1,161 @@
+use std::hash::Hash;
+
+use crate::{
+    account::{Account, DeepError, DeepNames},
+    stg::{Stg, StgError, StgTrait},
+};
+
+/// Trait for structs whose fields are settings of an [`Account`]
+///
+/// Each field of the struct corresponds to a setting of the `Account` with the field's name as key.
+///
+/// With the "derive" feature activated `Settings` can be derived for structs with named fields.
+/// The key of a field can be changed with `#[setting(rename = "...")]`, and a field marked with
+/// `#[setting(default)]` or `#[setting(default = "path::to::function")]` will use [`Default`] or
+/// the function provided when the setting isn't present in the `Account`.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "derive")]
+/// # {
+/// use hashmap_settings::{account::Account,stg::{Setting,Settings,Stg}};
+///
+/// #[derive(Debug, PartialEq, Settings)]
+/// struct EditorSettings {
+///     lines: i32,
+///     #[setting(rename = "editor word")]
+///     word: String,
+///     #[setting(default)]
+///     wrap: bool,
+/// }
+///
+/// let mut account = Account::<(),&str,Stg>::default();
+/// account.insert("lines", 3.stg());
+/// account.insert("editor word", "local".to_string().stg());
+///
+/// let settings = EditorSettings::from_account(&account)?;
+/// assert_eq!(settings, EditorSettings { lines: 3, word: "local".to_string(), wrap: false });
+/// # }
+/// # Ok::<(), hashmap_settings::stg::SettingsError>(())
+/// ```
+pub trait Settings: Sized {
+    /// Creates `Self` from the settings of an `Account`
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SettingsError`] containing every field that is missing or of the wrong type.
+    fn from_account<N, K: Clone + Eq + Hash + From<&'static str>, V>(
+        account: &Account<N, K, V>,
+    ) -> Result<Self, SettingsError>
+    where
+        for<'a> Option<&'a V>: StgTrait;
+    /// Inserts the fields of `self` as settings of a child `Account`, or of the `Account` itself
+    /// if `account_names` is empty.
+    ///
+    /// The fields are inserted in a [transaction](Account::transaction), so the parent `Accounts`
+    /// remain [valid](Account#valid) and either every field is written or none is.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`DeepError::NotFound`] if the child `Account` doesn't exist, or a
+    /// [`DeepError::Locked`] if one of the settings is [locked](Account::lock) below it.
+    /// The `Account` is left unchanged in both cases.
+    fn write_to<
+        'a,
+        N: Clone + PartialEq + 'a,
+        K: Clone + Eq + Hash + From<&'static str>,
+        V: Clone + From<Stg>,
+    >(
+        &self,
+        account: &mut Account<N, K, V>,
+        account_names: impl DeepNames<'a, N>,
+    ) -> Result<(), DeepError>;
+}
+
+/// Error returned by [`Settings::from_account`]
+///
+/// Contains the key of every setting that couldn't be obtained and the respective [`StgError`].
+#[derive(Debug, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct SettingsError {
+    errors: Vec<(&'static str, StgError)>,
+}
+impl SettingsError {
+    /// Creates a new `SettingsError`
+    ///
+    /// # Examples
+    /// ```
+    /// use hashmap_settings::stg::{SettingsError,StgError};
+    /// let error = SettingsError::new(vec![("lines", StgError::None)]);
+    /// assert_eq!(error.errors(), &vec![("lines", StgError::None)]);
+    /// ```
+    #[must_use]
+    pub const fn new(errors: Vec<(&'static str, StgError)>) -> Self {
+        Self { errors }
+    }
+    /// Returns the keys of the settings that couldn't be obtained and the respective [`StgError`].
+    #[must_use]
+    pub const fn errors(&self) -> &Vec<(&'static str, StgError)> {
+        &self.errors
+    }
+}
+
+#[doc(hidden)]
+pub mod __private {
+    //! Functions used by the code generated by `#[derive(Settings)]`, not public API.
+    use std::hash::Hash;
+
+    use crate::{
+        account::{Account, DeepError, DeepNames},
+        stg::{Setting, Stg, StgError, StgTrait},
+    };
+
+    pub fn field<N, K: Eq + Hash + From<&'static str>, V, S: Setting>(
+        account: &Account<N, K, V>,
+        key: &'static str,
+        default: Option<fn() -> S>,
+        errors: &mut Vec<(&'static str, StgError)>,
+    ) -> Option<S>
+    where
+        for<'a> Option<&'a V>: StgTrait,
+    {
+        match (account.get(&K::from(key)).unstg(), default) {
+            (Ok(value), _) => Some(value),
+            (Err(StgError::None), Some(default)) => Some(default()),
+            (Err(error), _) => {
+                errors.push((key, error));
+                None
+            }
+        }
+    }
+
+    pub fn stg<S: Setting>(value: &S) -> Stg {
+        dyn_clone::clone(value).stg()
+    }
+
+    pub fn write<
+        'a,
+        N: Clone + PartialEq + 'a,
+        K: Clone + Eq + Hash + From<&'static str>,
+        V: Clone + From<Stg>,
+    >(
+        account: &mut Account<N, K, V>,
+        account_names: impl DeepNames<'a, N>,
+        settings: Vec<(&'static str, Stg)>,
+    ) -> Result<(), DeepError> {
+        let account_names = account_names.deep_names();
+        if account_names.is_empty() {
+            for (key, value) in settings {
+                account.insert(K::from(key), value.into());
+            }
+            return Ok(());
+        }
+        account.transaction(|transaction| {
+            for (key, value) in settings {
+                transaction.deep_insert(&K::from(key), value.into(), &mut account_names.clone())?;
+            }
+            Ok(())
+        })
+    }
+}